@@ -0,0 +1,175 @@
+// Every shuffle moves the card at position x to position a*x + b (mod n), and
+// so does any sequence of them, which is what makes huge decks tractable: the
+// whole input collapses to one map that can be repeated or undone in
+// O(log k) without ever laying the cards out.
+
+use crate::instruction::Instruction;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Affine {
+    pub a: u64,
+    pub b: u64,
+    pub n: u64,
+}
+
+fn mul_mod(x: u64, y: u64, n: u64) -> u64 {
+    ((u128::from(x) * u128::from(y)) % u128::from(n)) as u64
+}
+
+fn add_mod(x: u64, y: u64, n: u64) -> u64 {
+    ((u128::from(x) + u128::from(y)) % u128::from(n)) as u64
+}
+
+// a shuffle argument, which may be negative or larger than the deck
+pub(crate) fn reduce(value: i64, n: u64) -> u64 {
+    i128::from(value).rem_euclid(i128::from(n)) as u64
+}
+
+// the x for which a*x = 1 (mod n), if a and n are coprime
+pub fn mod_inverse(a: u64, n: u64) -> Option<u64> {
+    let (mut r0, mut r1) = (i128::from(n), i128::from(a % n));
+    let (mut t0, mut t1) = (0i128, 1i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        let (r, t) = (r0 - q * r1, t0 - q * t1);
+        r0 = r1;
+        r1 = r;
+        t0 = t1;
+        t1 = t;
+    }
+    if r0 != 1 {
+        return None;
+    }
+    Some(t0.rem_euclid(i128::from(n)) as u64)
+}
+
+impl Affine {
+    pub fn identity(n: u64) -> Affine {
+        Affine { a: 1, b: 0, n }
+    }
+
+    pub fn from_instruction(instruction: &Instruction, n: u64) -> Affine {
+        match *instruction {
+            Instruction::Cut(k) => Affine {
+                a: 1,
+                b: reduce(-i64::from(k), n),
+                n,
+            },
+            Instruction::DealIntoNewStack => Affine {
+                a: n - 1,
+                b: n - 1,
+                n,
+            },
+            Instruction::DealWithIncrement(k) => Affine {
+                a: reduce(i64::from(k), n),
+                b: 0,
+                n,
+            },
+        }
+    }
+
+    pub fn from_instructions(instructions: &[Instruction], n: u64) -> Affine {
+        instructions.iter().fold(Affine::identity(n), |map, i| {
+            map.then(&Affine::from_instruction(i, n))
+        })
+    }
+
+    // this map followed by `next`
+    pub fn then(&self, next: &Affine) -> Affine {
+        Affine {
+            a: mul_mod(next.a, self.a, self.n),
+            b: add_mod(mul_mod(next.a, self.b, self.n), next.b, self.n),
+            n: self.n,
+        }
+    }
+
+    // this map applied k times over, by repeated squaring
+    pub fn pow(&self, k: u64) -> Affine {
+        let mut result = Affine::identity(self.n);
+        let mut square = *self;
+        let mut k = k;
+        while k > 0 {
+            if k & 1 == 1 {
+                result = result.then(&square);
+            }
+            square = square.then(&square);
+            k >>= 1;
+        }
+        result
+    }
+
+    // `None` if the map isn't a permutation, e.g. dealing with an increment
+    // that shares a factor with the deck size
+    pub fn inverse(&self) -> Option<Affine> {
+        let a = mod_inverse(self.a, self.n)?;
+        Some(Affine {
+            a,
+            b: mul_mod(a, self.n - self.b % self.n, self.n),
+            n: self.n,
+        })
+    }
+
+    pub fn apply(&self, x: u64) -> u64 {
+        add_mod(mul_mod(self.a, x % self.n, self.n), self.b, self.n)
+    }
+}
+
+// where card `card` of a fresh deck of `n` ends up after shuffling `k` times
+pub fn position_of(instructions: &[Instruction], n: u64, k: u64, card: u64) -> u64 {
+    Affine::from_instructions(instructions, n)
+        .pow(k)
+        .apply(card)
+}
+
+// which card lies at `position` after shuffling a fresh deck of `n` `k` times
+pub fn card_at(instructions: &[Instruction], n: u64, k: u64, position: u64) -> Option<u64> {
+    let inverse = Affine::from_instructions(instructions, n)
+        .pow(k)
+        .inverse()?;
+    Some(inverse.apply(position))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples::{self, DECK_SIZE};
+    use std::path::Path;
+
+    #[test]
+    fn test_examples() {
+        for (instructions, cards) in examples::all() {
+            for (position, &card) in cards.iter().enumerate() {
+                let position = position as u64;
+                assert_eq!(position_of(&instructions, DECK_SIZE, 1, card), position);
+                assert_eq!(card_at(&instructions, DECK_SIZE, 1, position), Some(card));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pow_and_inverse() {
+        let instructions = examples::longest();
+        let map = Affine::from_instructions(&instructions, 10007);
+        let mut repeated = Affine::identity(10007);
+        for _ in 0..13 {
+            repeated = repeated.then(&map);
+        }
+        assert_eq!(map.pow(13), repeated);
+        assert_eq!(
+            map.pow(13).then(&map.pow(13).inverse().unwrap()),
+            Affine::identity(10007)
+        );
+        assert_eq!(mod_inverse(3, 10), Some(7));
+        assert_eq!(mod_inverse(4, 10), None);
+    }
+
+    #[test]
+    fn test_huge_deck_round_trip() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("input.txt");
+        let instructions = Instruction::load(path).unwrap();
+        assert_eq!(position_of(&instructions, 10007, 1, 2019), 3377);
+        let (n, k) = (119_315_717_514_047, 101_741_582_076_661);
+        let card = card_at(&instructions, n, k, 2020).unwrap();
+        assert_eq!(position_of(&instructions, n, k, card), 2020);
+    }
+}
@@ -0,0 +1,295 @@
+// The structure of a shuffle as a permutation of the deck: how many times it
+// has to be repeated to get back to factory order, which cards never move,
+// and the lengths of its cycles. On a prime number of cards x -> a*x + b
+// settles all of these directly; anything else is worked out by following
+// every card.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::affine::{mod_inverse, Affine};
+use crate::deck::{Deck, ShuffleError};
+use crate::instruction::Instruction;
+
+// decks beyond this are too big to walk card by card
+pub const WALK_LIMIT: u64 = 10_000_000;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FixedPoints {
+    // the shuffle leaves the deck as it was
+    All,
+    Cards(Vec<u64>),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Analysis {
+    pub order: u64,
+    pub fixed_points: FixedPoints,
+    // (cycle length, number of cycles of that length), shortest first
+    pub cycle_type: Vec<(u64, u64)>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AnalysisError {
+    Shuffle(ShuffleError),
+    TooLarge(u64),
+}
+
+impl From<ShuffleError> for AnalysisError {
+    fn from(e: ShuffleError) -> Self {
+        AnalysisError::Shuffle(e)
+    }
+}
+
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnalysisError::Shuffle(e) => write!(f, "{}", e),
+            AnalysisError::TooLarge(n) => write!(
+                f,
+                "{} cards is too many to follow one by one (the limit is {})",
+                n, WALK_LIMIT
+            ),
+        }
+    }
+}
+
+fn pow_mod(a: u64, k: u64, n: u64) -> u64 {
+    Affine { a, b: 0, n }.pow(k).a
+}
+
+// deterministic Miller-Rabin; these bases cover every u64
+pub fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    if let Some(&p) = BASES.iter().find(|&&p| n.is_multiple_of(p)) {
+        return n == p;
+    }
+    let (mut d, mut s) = (n - 1, 0);
+    while d % 2 == 0 {
+        d /= 2;
+        s += 1;
+    }
+    BASES.iter().all(|&base| {
+        let mut x = pow_mod(base, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = ((u128::from(x) * u128::from(x)) % u128::from(n)) as u64;
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+fn prime_factors(mut n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            factors.push(p);
+            while n.is_multiple_of(p) {
+                n /= p;
+            }
+        }
+        p += if p == 2 { 1 } else { 2 };
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
+}
+
+// the smallest k > 0 with a^k = 1 (mod p), for prime p not dividing a
+fn multiplicative_order(a: u64, p: u64) -> u64 {
+    let mut order = p - 1;
+    for q in prime_factors(p - 1) {
+        while order.is_multiple_of(q) && pow_mod(a, order / q, p) == 1 {
+            order /= q;
+        }
+    }
+    order
+}
+
+// `None` unless the deck size is prime and the map is a permutation
+pub fn analyse_affine(map: &Affine) -> Option<Analysis> {
+    let p = map.n;
+    if !is_prime(p) || map.a.is_multiple_of(p) {
+        return None;
+    }
+    let (a, b) = (map.a % p, map.b % p);
+    Some(if a == 1 && b == 0 {
+        Analysis {
+            order: 1,
+            fixed_points: FixedPoints::All,
+            cycle_type: vec![(1, p)],
+        }
+    } else if a == 1 {
+        // a pure cut moves every card round one big cycle
+        Analysis {
+            order: p,
+            fixed_points: FixedPoints::Cards(Vec::new()),
+            cycle_type: vec![(p, 1)],
+        }
+    } else {
+        // one card stays put at b / (1 - a); around it the map is x -> a*x,
+        // so every other card is on a cycle as long as the order of a
+        let fixed = Affine {
+            a: mod_inverse(p + 1 - a, p)?,
+            b: 0,
+            n: p,
+        }
+        .apply(b);
+        let order = multiplicative_order(a, p);
+        Analysis {
+            order,
+            fixed_points: FixedPoints::Cards(vec![fixed]),
+            cycle_type: vec![(1, 1), (order, (p - 1) / order)],
+        }
+    })
+}
+
+// where each card of a fresh deck of `n` ends up
+fn destinations(instructions: &[Instruction], n: u64) -> Result<Vec<u64>, AnalysisError> {
+    if n > WALK_LIMIT {
+        return Err(AnalysisError::TooLarge(n));
+    }
+    let deck = Deck::new(n).shuffle(instructions)?;
+    let mut destinations = vec![0; n as usize];
+    for (position, &card) in deck.iter().enumerate() {
+        destinations[card as usize] = position as u64;
+    }
+    Ok(destinations)
+}
+
+// every cycle, each starting from its lowest card, in order of that card
+pub fn cycles(instructions: &[Instruction], n: u64) -> Result<Vec<Vec<u64>>, AnalysisError> {
+    let destinations = destinations(instructions, n)?;
+    let mut seen = vec![false; destinations.len()];
+    let mut cycles = Vec::new();
+    for start in 0..destinations.len() {
+        if seen[start] {
+            continue;
+        }
+        let mut cycle = Vec::new();
+        let mut card = start;
+        while !seen[card] {
+            seen[card] = true;
+            cycle.push(card as u64);
+            card = destinations[card] as usize;
+        }
+        cycles.push(cycle);
+    }
+    Ok(cycles)
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+pub fn analyse_walk(instructions: &[Instruction], n: u64) -> Result<Analysis, AnalysisError> {
+    let cycles = cycles(instructions, n)?;
+    let mut lengths: BTreeMap<u64, u64> = BTreeMap::new();
+    for cycle in cycles.iter() {
+        *lengths.entry(cycle.len() as u64).or_insert(0) += 1;
+    }
+    let order = lengths
+        .keys()
+        .fold(1, |order, &l| order / gcd(order, l) * l);
+    let fixed: Vec<u64> = cycles
+        .iter()
+        .filter(|cycle| cycle.len() == 1)
+        .map(|cycle| cycle[0])
+        .collect();
+    Ok(Analysis {
+        order,
+        fixed_points: if fixed.len() as u64 == n {
+            FixedPoints::All
+        } else {
+            FixedPoints::Cards(fixed)
+        },
+        cycle_type: lengths.into_iter().collect(),
+    })
+}
+
+pub fn analyse(instructions: &[Instruction], n: u64) -> Result<Analysis, AnalysisError> {
+    if is_prime(n) {
+        let map = Affine::from_instructions(instructions, n);
+        if let Some(analysis) = analyse_affine(&map) {
+            return Ok(analysis);
+        }
+    }
+    analyse_walk(instructions, n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples;
+    use std::path::Path;
+
+    fn input() -> Vec<Instruction> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("input.txt");
+        Instruction::load(path).unwrap()
+    }
+
+    #[test]
+    fn test_is_prime() {
+        let primes: Vec<u64> = (0..30).filter(|&n| is_prime(n)).collect();
+        assert_eq!(primes, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert!(is_prime(10007));
+        assert!(is_prime(119_315_717_514_047));
+        assert!(!is_prime(119_315_717_514_049));
+    }
+
+    #[test]
+    fn test_affine_matches_walk() {
+        let instructions = input();
+        for &n in [10007, 101, 13].iter() {
+            let map = Affine::from_instructions(&instructions, n);
+            if let Some(analysis) = analyse_affine(&map) {
+                assert_eq!(analysis, analyse_walk(&instructions, n).unwrap());
+                assert_eq!(map.pow(analysis.order), Affine::identity(n));
+            }
+        }
+        use Instruction::*;
+        for list in [vec![], vec![Cut(3)], vec![DealIntoNewStack]].iter() {
+            let map = Affine::from_instructions(list, 11);
+            assert_eq!(analyse_affine(&map), analyse_walk(list, 11).ok());
+        }
+    }
+
+    #[test]
+    fn test_huge_deck() {
+        let n = 119_315_717_514_047;
+        let analysis = analyse(&input(), n).unwrap();
+        let map = Affine::from_instructions(&input(), n);
+        assert_eq!(map.pow(analysis.order), Affine::identity(n));
+        if let FixedPoints::Cards(cards) = analysis.fixed_points {
+            assert!(cards.iter().all(|&c| map.apply(c) == c));
+        }
+    }
+
+    #[test]
+    fn test_composite_deck() {
+        // the longest example from the puzzle: 9 2 5 8 1 4 7 0 3 6
+        let instructions = examples::longest();
+        assert_eq!(
+            cycles(&instructions, 10).unwrap(),
+            vec![vec![0, 7, 6, 9], vec![1, 4, 5, 2], vec![3, 8]]
+        );
+        let analysis = analyse(&instructions, 10).unwrap();
+        assert_eq!(analysis.order, 4);
+        assert_eq!(analysis.fixed_points, FixedPoints::Cards(vec![]));
+        assert_eq!(analysis.cycle_type, vec![(2, 1), (4, 2)]);
+    }
+}
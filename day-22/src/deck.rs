@@ -0,0 +1,250 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Index;
+use std::slice;
+use std::vec;
+
+use crate::affine::mod_inverse;
+use crate::instruction::Instruction;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ShuffleError {
+    // dealing would land more than one card on some positions
+    IncrementNotCoprime { increment: i32, len: usize },
+}
+
+impl fmt::Display for ShuffleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShuffleError::IncrementNotCoprime { increment, len } => write!(
+                f,
+                "can't deal {} cards with increment {}: they share a factor",
+                len, increment
+            ),
+        }
+    }
+}
+
+pub fn deal_into_new<T>(mut input: Vec<T>) -> Vec<T> {
+    input.reverse();
+    input
+}
+
+// cuts may be negative or bigger than the deck, and wrap around
+pub fn cut<T>(mut input: Vec<T>, n: i32) -> Vec<T> {
+    if !input.is_empty() {
+        let at = i64::from(n).rem_euclid(input.len() as i64) as usize;
+        input.rotate_left(at);
+    }
+    input
+}
+
+// the card dealt to position j is the one at j / increment, so the output is
+// built in order with a single allocation
+pub fn deal_with_increment<T: Clone>(
+    input: Vec<T>,
+    increment: i32,
+) -> Result<Vec<T>, ShuffleError> {
+    let len = input.len();
+    if len <= 1 {
+        return Ok(input);
+    }
+    let step = i64::from(increment).rem_euclid(len as i64) as u64;
+    let inverse = mod_inverse(step, len as u64)
+        .ok_or(ShuffleError::IncrementNotCoprime { increment, len })? as usize;
+    let mut output = Vec::with_capacity(len);
+    let mut source = 0;
+    for _ in 0..len {
+        output.push(input[source].clone());
+        source = ((source as u128 + inverse as u128) % len as u128) as usize;
+    }
+    Ok(output)
+}
+
+// cards can be anything; a fresh deck from `Deck::new` is numbered from 0
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Deck<T = u64>(Vec<T>);
+
+impl Deck<u64> {
+    pub fn new(n: u64) -> Deck<u64> {
+        Deck((0..n).collect())
+    }
+}
+
+impl<T: Clone> Deck<T> {
+    pub fn cut(self, n: i32) -> Deck<T> {
+        Deck(cut(self.0, n))
+    }
+
+    pub fn deal_into_new(self) -> Deck<T> {
+        Deck(deal_into_new(self.0))
+    }
+
+    pub fn deal_with_increment(self, n: i32) -> Result<Deck<T>, ShuffleError> {
+        Ok(Deck(deal_with_increment(self.0, n)?))
+    }
+
+    pub fn apply(self, instruction: &Instruction) -> Result<Deck<T>, ShuffleError> {
+        match *instruction {
+            Instruction::Cut(n) => Ok(self.cut(n)),
+            Instruction::DealIntoNewStack => Ok(self.deal_into_new()),
+            Instruction::DealWithIncrement(n) => self.deal_with_increment(n),
+        }
+    }
+
+    pub fn shuffle(self, instructions: &[Instruction]) -> Result<Deck<T>, ShuffleError> {
+        instructions.iter().try_fold(self, |deck, i| deck.apply(i))
+    }
+}
+
+impl<T> Deck<T> {
+    pub fn len(&self) -> u64 {
+        self.0.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, position: u64) -> Option<&T> {
+        self.0.get(usize::try_from(position).ok()?)
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.0.iter()
+    }
+
+    pub fn position_of(&self, card: &T) -> Option<u64>
+    where
+        T: PartialEq,
+    {
+        self.0.iter().position(|c| c == card).map(|p| p as u64)
+    }
+
+    pub fn vec(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> Index<u64> for Deck<T> {
+    type Output = T;
+
+    fn index(&self, position: u64) -> &T {
+        &self.0[position as usize]
+    }
+}
+
+impl<T> From<Vec<T>> for Deck<T> {
+    fn from(v: Vec<T>) -> Deck<T> {
+        Deck(v)
+    }
+}
+
+impl<T> FromIterator<T> for Deck<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Deck<T> {
+        Deck(iter.into_iter().collect())
+    }
+}
+
+impl<T> IntoIterator for Deck<T> {
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
+
+    fn into_iter(self) -> vec::IntoIter<T> {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Deck<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples::{self, DECK_SIZE};
+
+    #[test]
+    fn test_cut_positive() {
+        assert_eq!(cut(vec![0, 1, 2, 3, 4, 5], 3), vec![3, 4, 5, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_cut_negative() {
+        assert_eq!(cut(vec![0, 1, 2, 3, 4, 5], -2), vec![4, 5, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_deal_with_increment() {
+        assert_eq!(
+            deal_with_increment(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9], 3),
+            Ok(vec![0, 7, 4, 1, 8, 5, 2, 9, 6, 3])
+        );
+    }
+
+    #[test]
+    fn test_cut_wraps() {
+        assert_eq!(cut(vec![0, 1, 2, 3, 4, 5], 9), vec![3, 4, 5, 0, 1, 2]);
+        assert_eq!(cut(vec![0, 1, 2, 3, 4, 5], -8), vec![4, 5, 0, 1, 2, 3]);
+        assert_eq!(cut(Vec::<i32>::new(), 3), vec![]);
+    }
+
+    #[test]
+    fn test_deal_with_negative_increment() {
+        // -7 is 3 modulo 10
+        assert_eq!(
+            deal_with_increment(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9], -7),
+            deal_with_increment(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9], 3)
+        );
+    }
+
+    #[test]
+    fn test_deal_needs_coprime_increment() {
+        assert_eq!(
+            Deck::new(10).deal_with_increment(4),
+            Err(ShuffleError::IncrementNotCoprime {
+                increment: 4,
+                len: 10
+            })
+        );
+        assert!(Deck::new(10).deal_with_increment(0).is_err());
+    }
+
+    #[test]
+    fn test_examples() {
+        for (instructions, cards) in examples::all() {
+            let deck = Deck::new(DECK_SIZE).shuffle(&instructions).unwrap();
+            assert_eq!(deck.vec(), cards);
+        }
+    }
+
+    #[test]
+    fn test_named_cards() {
+        let suits = ["clubs", "diamonds", "hearts", "spades"];
+        let cards: Deck<String> = suits
+            .iter()
+            .flat_map(|suit| (1..=13).map(move |rank| format!("{} of {}", rank, suit)))
+            .collect();
+        let shuffled = cards
+            .clone()
+            .cut(-3)
+            .deal_with_increment(5)
+            .unwrap()
+            .deal_into_new();
+        assert_eq!(shuffled.len(), 52);
+        let ace = "1 of clubs".to_string();
+        let position = shuffled.position_of(&ace).unwrap();
+        assert_eq!(shuffled[position], ace);
+        assert_eq!(
+            shuffled.iter().filter(|c| c.ends_with("hearts")).count(),
+            13
+        );
+        assert_eq!(cards.get(52), None);
+    }
+}
@@ -0,0 +1,53 @@
+// The four examples from the puzzle, each shuffling a deck of ten cards, for
+// every engine's tests to check itself against.
+
+use crate::instruction::Instruction;
+
+const EXAMPLES: [(&str, [u64; 10]); 4] = [
+    (
+        "deal with increment 7
+deal into new stack
+deal into new stack",
+        [0, 3, 6, 9, 2, 5, 8, 1, 4, 7],
+    ),
+    (
+        "cut 6
+deal with increment 7
+deal into new stack",
+        [3, 0, 7, 4, 1, 8, 5, 2, 9, 6],
+    ),
+    (
+        "deal with increment 7
+deal with increment 9
+cut -2",
+        [6, 3, 0, 7, 4, 1, 8, 5, 2, 9],
+    ),
+    (
+        "deal into new stack
+cut -2
+deal with increment 7
+cut 8
+cut -4
+deal with increment 7
+cut 3
+deal with increment 9
+deal with increment 3
+cut -1",
+        [9, 2, 5, 8, 1, 4, 7, 0, 3, 6],
+    ),
+];
+
+pub const DECK_SIZE: u64 = 10;
+
+// each example's instructions, and the cards they leave from the top down
+pub fn all() -> Vec<(Vec<Instruction>, Vec<u64>)> {
+    EXAMPLES
+        .iter()
+        .map(|(text, cards)| (Instruction::parse_list(text).unwrap(), cards.to_vec()))
+        .collect()
+}
+
+// the longest of them, which uses every kind of instruction
+pub fn longest() -> Vec<Instruction> {
+    all().pop().unwrap().0
+}
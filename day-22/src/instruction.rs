@@ -0,0 +1,156 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::num::ParseIntError;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Instruction {
+    Cut(i32),
+    DealIntoNewStack,
+    DealWithIncrement(i32),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InstructionParseError {
+    IntError(ParseIntError),
+    UnknownInstruction(String),
+}
+
+impl From<ParseIntError> for InstructionParseError {
+    fn from(e: ParseIntError) -> Self {
+        InstructionParseError::IntError(e)
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = InstructionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "deal into new stack" {
+            Ok(Instruction::DealIntoNewStack)
+        } else if let Some(n) = s.strip_prefix("cut ") {
+            Ok(Instruction::Cut(i32::from_str(n.trim())?))
+        } else if let Some(n) = s.strip_prefix("deal with increment ") {
+            Ok(Instruction::DealWithIncrement(i32::from_str(n.trim())?))
+        } else {
+            Err(InstructionParseError::UnknownInstruction(s.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instruction::Cut(n) => write!(f, "cut {}", n),
+            Instruction::DealIntoNewStack => write!(f, "deal into new stack"),
+            Instruction::DealWithIncrement(n) => write!(f, "deal with increment {}", n),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    // lines are numbered from 1
+    Instruction(usize, InstructionParseError),
+}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "{}", e),
+            ParseError::Instruction(line, InstructionParseError::IntError(e)) => {
+                write!(f, "line {}: {}", line, e)
+            }
+            ParseError::Instruction(line, InstructionParseError::UnknownInstruction(s)) => {
+                write!(f, "line {}: unknown instruction {:?}", line, s)
+            }
+        }
+    }
+}
+
+impl Instruction {
+    // one instruction per line; blank lines are skipped
+    pub fn parse_list(s: &str) -> Result<Vec<Instruction>, ParseError> {
+        s.lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .map(|(i, l)| Instruction::from_str(l).map_err(|e| ParseError::Instruction(i + 1, e)))
+            .collect()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Instruction>, ParseError> {
+        Instruction::parse_list(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::Deck;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            Instruction::from_str("deal into new stack"),
+            Ok(Instruction::DealIntoNewStack)
+        );
+        assert_eq!(Instruction::from_str("cut -2"), Ok(Instruction::Cut(-2)));
+        assert_eq!(
+            Instruction::from_str("deal with increment 7"),
+            Ok(Instruction::DealWithIncrement(7))
+        );
+        assert_eq!(
+            Instruction::from_str("shuffle"),
+            Err(InstructionParseError::UnknownInstruction(
+                "shuffle".to_string()
+            ))
+        );
+        assert!(Instruction::from_str("cut x").is_err());
+    }
+
+    #[test]
+    fn test_parse_list_reports_line() {
+        match Instruction::parse_list("cut 3\n\ndeal with increment\n") {
+            Err(ParseError::Instruction(3, InstructionParseError::UnknownInstruction(_))) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_apply_parsed() {
+        let instructions = Instruction::parse_list(
+            "deal into new stack
+cut -2
+deal with increment 7
+cut 8
+cut -4
+deal with increment 7
+cut 3
+deal with increment 9
+deal with increment 3
+cut -1
+",
+        )
+        .unwrap();
+        assert_eq!(
+            Deck::new(10).shuffle(&instructions).unwrap().vec(),
+            vec![9, 2, 5, 8, 1, 4, 7, 0, 3, 6]
+        );
+    }
+
+    #[test]
+    fn test_load_input() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("input.txt");
+        assert_eq!(Instruction::load(path).unwrap().len(), 100);
+    }
+}
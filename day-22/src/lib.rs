@@ -0,0 +1,9 @@
+pub mod affine;
+pub mod analysis;
+pub mod deck;
+#[cfg(test)]
+mod examples;
+pub mod instruction;
+pub mod persistent;
+pub mod position;
+pub mod simplify;
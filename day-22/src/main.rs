@@ -1,166 +1,309 @@
-use std::fmt::Debug;
-use std::fs::File;
-use std::io::BufReader;
-use std::io::prelude::*;
-use std::path::Path;
-
-fn deal_into_new<T: Clone>(mut input: Vec<T>) -> Vec<T> {
-    input.reverse();
-    input
-}
-
-fn cut<T: Clone>(mut input: Vec<T>, n: i32) -> Vec<T> {
-    if n == 0 {
-        input
-    } else if n > 0 {
-        let mut tail = input.split_off(n as usize);
-        tail.append(&mut input);
-        tail
-    } else {
-        let offset: i32 = (input.len() as i32) + (n as i32);
-        let mut tail = input.split_off(offset as usize);
-        tail.append(&mut input);
-        tail
-    }
-}
-
-fn deal_with_increment<T: Clone + Debug>(input: Vec<T>, increment: i32) -> Vec<T> {
-    let width = input.len();
-    let mut output: Vec<Option<T>> = {
-        let mut output = Vec::new();
-        for _ in 0..width {
-            output.push(None);
-        }
-        output
-    };
-    let mut offset: usize = 0;
-    for i in input.iter() {
-        output.get_mut(offset).unwrap().replace(i.clone());
-        println!("output: {:?}", output);
-        offset = offset + (increment as usize);
-        offset = offset % (width as usize);
-    }
-    let real_output: Option<Vec<T>> = output.into_iter().collect();
-    real_output.unwrap()
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+
+use day_22::affine::{self, Affine};
+use day_22::analysis::{self, FixedPoints};
+use day_22::deck::Deck;
+use day_22::instruction::Instruction;
+use day_22::simplify;
+
+const USAGE: &str = "usage: day-22 [options] [COMMAND]
+
+  --deck-size N         the number of cards, 10007 by default
+  --repeat K            shuffle K times over, once by default
+  --input FILE          the shuffle instructions, input.txt by default
+  --engine ENGINE       `closed-form` (the default) or `vec`, which lays out
+                        every card
+  --cross-check         answer with both engines and check they agree
+
+commands:
+  where-is CARD         the position CARD ends up at
+  card-at POSITION      the card that ends up at POSITION
+  dump                  every card, in order, for small decks
+  simplify              the shortest shuffle that does the same thing
+  analyse               the order, fixed points and cycles of the shuffle
+
+Without a command, the answers to both parts of the puzzle are printed;
+--deck-size and --repeat only apply to a command.";
+
+// decks bigger than this are never laid out card by card
+const VEC_LIMIT: u64 = 10_000_000;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Engine {
+    Vec,
+    ClosedForm,
 }
 
-#[derive(PartialEq, Eq, Debug)]
-struct Deck(Vec<i32>);
+impl FromStr for Engine {
+    type Err = String;
 
-impl Deck {
-    fn new(n: i32) -> Deck {
-        Deck((0..n).collect())
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "vec" => Ok(Engine::Vec),
+            "closed-form" => Ok(Engine::ClosedForm),
+            _ => Err(format!("unknown engine {}", s)),
+        }
     }
+}
 
-    fn cut(self, n: i32) -> Deck {
-        Deck(cut(self.0, n))
-    }
+enum Command {
+    Answers,
+    WhereIs(u64),
+    CardAt(u64),
+    Dump,
+    Simplify,
+    Analyse,
+}
 
-    fn deal_into_new(self) -> Deck {
-        Deck(deal_into_new(self.0))
-    }
+struct Options {
+    deck_size: u64,
+    repeat: u64,
+    input: PathBuf,
+    engine: Engine,
+    cross_check: bool,
+    command: Command,
+}
 
-    fn deal_with_increment(self, n: i32) -> Deck {
-        Deck(deal_with_increment(self.0, n))
-    }
+fn parse_number(name: &str, value: &str) -> Result<u64, String> {
+    u64::from_str(value).map_err(|_| format!("{} needs a number, not {:?}", name, value))
+}
 
-    fn vec(self) -> Vec<i32> {
-        self.0
+fn parse_args(mut args: env::Args) -> Result<Options, String> {
+    args.next();
+    let mut options = Options {
+        deck_size: 10007,
+        repeat: 1,
+        input: Path::new(env!("CARGO_MANIFEST_DIR")).join("input.txt"),
+        engine: Engine::ClosedForm,
+        cross_check: false,
+        command: Command::Answers,
+    };
+    let mut command = None;
+    // the answers are for the puzzle's own decks, so these need a command
+    let mut shape = None;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+        match arg.as_str() {
+            "--deck-size" => {
+                options.deck_size = parse_number(&arg, &value(&arg)?)?;
+                shape.get_or_insert(arg);
+            }
+            "--repeat" => {
+                options.repeat = parse_number(&arg, &value(&arg)?)?;
+                shape.get_or_insert(arg);
+            }
+            "--input" => options.input = PathBuf::from(value(&arg)?),
+            "--engine" => options.engine = Engine::from_str(&value(&arg)?)?,
+            "--cross-check" => options.cross_check = true,
+            _ if arg.starts_with("--") || command.is_some() => {
+                return Err(format!("unexpected argument {}", arg))
+            }
+            "where-is" => command = Some(Command::WhereIs(parse_number(&arg, &value(&arg)?)?)),
+            "card-at" => command = Some(Command::CardAt(parse_number(&arg, &value(&arg)?)?)),
+            "dump" => command = Some(Command::Dump),
+            "simplify" => command = Some(Command::Simplify),
+            "analyse" => command = Some(Command::Analyse),
+            _ => return Err(format!("unknown command {}", arg)),
+        }
     }
+    if options.deck_size == 0 {
+        return Err("the deck needs at least one card".to_string());
+    }
+    match command {
+        Some(Command::WhereIs(card)) if card >= options.deck_size => {
+            return Err(format!("there is no card {}", card))
+        }
+        Some(Command::CardAt(position)) if position >= options.deck_size => {
+            return Err(format!("there is no position {}", position))
+        }
+        Some(command) => options.command = command,
+        None => {
+            if let Some(flag) = shape {
+                return Err(format!("{} needs a command", flag));
+            }
+        }
+    }
+    Ok(options)
 }
 
-impl From<Vec<i32>> for Deck {
-    fn from(v: Vec<i32>) -> Deck {
-        Deck(v)
+fn too_large(options: &Options) -> Result<(), String> {
+    if options.deck_size > VEC_LIMIT {
+        return Err(format!(
+            "{} cards is too many to lay out (the limit is {})",
+            options.deck_size, VEC_LIMIT
+        ));
     }
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_cut_positive() {
-        assert_eq!(cut(vec![0, 1, 2, 3, 4, 5], 3), vec![3, 4, 5, 0, 1, 2]);
+// the deck after one shuffle, then each card followed round its cycle the
+// remainder of `repeat` over the cycle's length, rather than shuffling the
+// whole deck over and over
+fn shuffled(instructions: &[Instruction], options: &Options) -> Result<Deck, String> {
+    too_large(options)?;
+    let once = Deck::new(options.deck_size)
+        .shuffle(instructions)
+        .map_err(|e| e.to_string())?;
+    if options.repeat == 1 {
+        return Ok(once);
     }
+    let mut cards = vec![0; once.len() as usize];
+    let mut seen = vec![false; cards.len()];
+    for start in 0..cards.len() {
+        if seen[start] {
+            continue;
+        }
+        let mut cycle = Vec::new();
+        let mut position = start;
+        while !seen[position] {
+            seen[position] = true;
+            cycle.push(position);
+            position = once[position as u64] as usize;
+        }
+        let turn = (options.repeat % cycle.len() as u64) as usize;
+        for (i, &position) in cycle.iter().enumerate() {
+            cards[position] = cycle[(i + turn) % cycle.len()] as u64;
+        }
+    }
+    Ok(Deck::from(cards))
+}
 
-    #[test]
-    fn test_cut_negative() {
-        assert_eq!(cut(vec![0, 1, 2, 3, 4, 5], -2), vec![4, 5, 0, 1, 2, 3]);
+fn closed_form(instructions: &[Instruction], options: &Options) -> Result<Affine, String> {
+    let map = Affine::from_instructions(instructions, options.deck_size).pow(options.repeat);
+    match map.inverse() {
+        Some(_) => Ok(map),
+        None => Err("the shuffle doesn't put every card somewhere".to_string()),
     }
+}
 
-    #[test]
-    fn test_deal_with_increment() {
-        assert_eq!(
-            deal_with_increment(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9], 3),
-            vec![0, 7, 4, 1, 8, 5, 2, 9, 6, 3]
-        );
+// the answer to a query from one engine
+fn answer(
+    instructions: &[Instruction],
+    options: &Options,
+    engine: Engine,
+) -> Result<Vec<u64>, String> {
+    match (engine, &options.command) {
+        (Engine::Vec, Command::WhereIs(card)) => {
+            let deck = shuffled(instructions, options)?;
+            Ok(deck.position_of(card).into_iter().collect())
+        }
+        (Engine::Vec, Command::CardAt(position)) => {
+            let deck = shuffled(instructions, options)?;
+            Ok(vec![deck[*position]])
+        }
+        (Engine::Vec, _) => Ok(shuffled(instructions, options)?.vec()),
+        (Engine::ClosedForm, Command::WhereIs(card)) => {
+            Ok(vec![closed_form(instructions, options)?.apply(*card)])
+        }
+        (Engine::ClosedForm, Command::CardAt(position)) => {
+            let inverse = closed_form(instructions, options)?.inverse().unwrap();
+            Ok(vec![inverse.apply(*position)])
+        }
+        (Engine::ClosedForm, _) => {
+            too_large(options)?;
+            let inverse = closed_form(instructions, options)?.inverse().unwrap();
+            Ok((0..options.deck_size).map(|p| inverse.apply(p)).collect())
+        }
     }
+}
 
-    #[test]
-    fn test_1() {
-        assert_eq!(
-            vec![0, 3, 6, 9, 2, 5, 8, 1, 4, 7],
-            Deck::new(10)
-                .deal_with_increment(7)
-                .deal_into_new()
-                .deal_into_new()
-                .vec()
-        );
+fn query(instructions: &[Instruction], options: &Options) -> Result<(), String> {
+    let result = answer(instructions, options, options.engine)?;
+    if options.cross_check {
+        let other = match options.engine {
+            Engine::Vec => Engine::ClosedForm,
+            Engine::ClosedForm => Engine::Vec,
+        };
+        if answer(instructions, options, other)? != result {
+            return Err("the vec and closed-form engines disagree".to_string());
+        }
     }
+    let cards: Vec<String> = result.iter().map(u64::to_string).collect();
+    println!("{}", cards.join(" "));
+    Ok(())
+}
+
+// the whole shuffle, repeats and all, as at most three instructions
+fn simplified(instructions: &[Instruction], options: &Options) -> Result<Vec<Instruction>, String> {
+    Affine::from_instructions(instructions, options.deck_size)
+        .pow(options.repeat)
+        .to_instructions()
+        .map_err(|e| e.to_string())
+}
 
-    #[test]
-    fn test_2() {
-        assert_eq!(
-            Deck::new(10)
-                .cut(6)
-                .deal_with_increment(7)
-                .deal_into_new()
-                .vec(),
-            vec![3, 0, 7, 4, 1, 8, 5, 2, 9, 6]
-        );
+// checked against the full list whenever the deck is small enough
+fn print_simplified(instructions: &[Instruction], options: &Options) -> Result<(), String> {
+    let simplified = simplified(instructions, options)?;
+    if options.deck_size <= VEC_LIMIT {
+        let expected = shuffled(instructions, options)?;
+        let actual = Deck::new(options.deck_size)
+            .shuffle(&simplified)
+            .map_err(|e| e.to_string())?;
+        if actual != expected {
+            return Err("the simplified shuffle doesn't match".to_string());
+        }
     }
+    print!("{}", simplify::format_list(&simplified));
+    Ok(())
+}
 
-    #[test]
-    fn test_3() {
-        assert_eq!(
-            Deck::new(10)
-                .deal_into_new()
-                .cut(-2)
-                .deal_with_increment(7)
-                .cut(8)
-                .cut(-4)
-                .deal_with_increment(7)
-                .cut(3)
-                .deal_with_increment(9)
-                .deal_with_increment(3)
-                .cut(-1)
-                .vec(),
-            vec![9, 2, 5, 8, 1, 4, 7, 0, 3, 6]
-        )
+fn print_analysis(instructions: &[Instruction], options: &Options) -> Result<(), String> {
+    let map = Affine::from_instructions(instructions, options.deck_size).pow(options.repeat);
+    let analysis = match analysis::analyse_affine(&map) {
+        Some(analysis) => analysis,
+        None if options.repeat == 1 => {
+            analysis::analyse(instructions, options.deck_size).map_err(|e| e.to_string())?
+        }
+        None => analysis::analyse(&simplified(instructions, options)?, options.deck_size)
+            .map_err(|e| e.to_string())?,
+    };
+    println!("order: {}", analysis.order);
+    match analysis.fixed_points {
+        FixedPoints::All => println!("fixed points: every card"),
+        FixedPoints::Cards(cards) => println!("fixed points: {:?}", cards),
+    }
+    for (length, count) in analysis.cycle_type {
+        println!("{} cycles of length {}", count, length);
     }
+    Ok(())
 }
 
-enum Instruction {
-    Cut(i32),
-    DealIntoNewStack,
-    DealWithIncrement(i32),
+fn print_answers(instructions: &[Instruction]) -> Result<(), String> {
+    let deck = Deck::new(10007)
+        .shuffle(instructions)
+        .map_err(|e| e.to_string())?;
+    println!("card 2019 is at position {:?}", deck.position_of(&2019));
+
+    let (size, repeats) = (119_315_717_514_047, 101_741_582_076_661);
+    match affine::card_at(instructions, size, repeats, 2020) {
+        Some(card) => println!("card at position 2020 is {}", card),
+        None => println!("the shuffle can't be undone for {} cards", size),
+    }
+    Ok(())
 }
 
-impl Instruction {
-    fn load(path: Path) -> std::io::Result<Vec<Instruction>> {
-        let file = File::open(path)?;
-        let mut reader = BufReader::new(file);
-        reader.lines().map(|l| {
-            
-        }).collect()
+fn run(options: Options) -> Result<(), String> {
+    let instructions = Instruction::load(&options.input)
+        .map_err(|e| format!("{}: {}", options.input.display(), e))?;
+    match options.command {
+        Command::Answers => print_answers(&instructions),
+        Command::Simplify => print_simplified(&instructions, &options),
+        Command::Analyse => print_analysis(&instructions, &options),
+        Command::WhereIs(_) | Command::CardAt(_) | Command::Dump => query(&instructions, &options),
     }
 }
 
-fn main() ->  std::io::Result<()> {
-    let mut v = Vec::new();
-    v.push(1);
-    v.push(2);
-    let x = deal_into_new(v);
-    println!("x: {:?}", x);
+fn main() {
+    let options = parse_args(env::args()).unwrap_or_else(|e| {
+        eprintln!("day-22: {}", e);
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    });
+    if let Err(e) = run(options) {
+        eprintln!("day-22: {}", e);
+        process::exit(1);
+    }
 }
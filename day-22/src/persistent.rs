@@ -0,0 +1,112 @@
+// A deck on a persistent vector: cuts are a split and an append rather than
+// a copy, and cloning shares structure, so earlier states can be kept around
+// for undo or comparison at little cost.
+
+use im::Vector;
+
+use crate::deck::{self, ShuffleError};
+use crate::instruction::Instruction;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PersistentDeck(Vector<u64>);
+
+impl PersistentDeck {
+    pub fn new(n: u64) -> PersistentDeck {
+        PersistentDeck((0..n).collect())
+    }
+
+    pub fn cut(self, n: i32) -> PersistentDeck {
+        let len = self.0.len() as i64;
+        if len == 0 {
+            return self;
+        }
+        let at = i64::from(n).rem_euclid(len) as usize;
+        let mut head = self.0;
+        let mut tail = head.split_off(at);
+        tail.append(head);
+        PersistentDeck(tail)
+    }
+
+    pub fn deal_into_new(self) -> PersistentDeck {
+        PersistentDeck(self.0.into_iter().rev().collect())
+    }
+
+    pub fn deal_with_increment(self, n: i32) -> Result<PersistentDeck, ShuffleError> {
+        let cards: Vec<u64> = self.0.into_iter().collect();
+        Ok(PersistentDeck(
+            deck::deal_with_increment(cards, n)?.into_iter().collect(),
+        ))
+    }
+
+    pub fn apply(self, instruction: &Instruction) -> Result<PersistentDeck, ShuffleError> {
+        match *instruction {
+            Instruction::Cut(n) => Ok(self.cut(n)),
+            Instruction::DealIntoNewStack => Ok(self.deal_into_new()),
+            Instruction::DealWithIncrement(n) => self.deal_with_increment(n),
+        }
+    }
+
+    pub fn shuffle(self, instructions: &[Instruction]) -> Result<PersistentDeck, ShuffleError> {
+        instructions.iter().try_fold(self, |deck, i| deck.apply(i))
+    }
+
+    pub fn vector(&self) -> &Vector<u64> {
+        &self.0
+    }
+
+    pub fn vec(self) -> Vec<u64> {
+        self.0.into_iter().collect()
+    }
+}
+
+impl From<Vec<u64>> for PersistentDeck {
+    fn from(v: Vec<u64>) -> PersistentDeck {
+        PersistentDeck(v.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::Deck;
+    use crate::examples::{self, DECK_SIZE};
+
+    #[test]
+    fn test_examples() {
+        for (instructions, cards) in examples::all() {
+            // every step of the way, as well as the end
+            for end in 0..instructions.len() {
+                let prefix = &instructions[..end];
+                let expected = Deck::new(DECK_SIZE).shuffle(prefix).unwrap();
+                let actual = PersistentDeck::new(DECK_SIZE).shuffle(prefix).unwrap();
+                assert_eq!(actual.vec(), expected.vec());
+            }
+            let deck = PersistentDeck::new(DECK_SIZE)
+                .shuffle(&instructions)
+                .unwrap();
+            assert_eq!(deck.vec(), cards);
+        }
+    }
+
+    #[test]
+    fn test_earlier_states_are_kept() {
+        let history: Vec<PersistentDeck> =
+            (0..5).fold(vec![PersistentDeck::new(10)], |mut history, i| {
+                let next = history
+                    .last()
+                    .unwrap()
+                    .clone()
+                    .cut(i + 1)
+                    .deal_with_increment(3)
+                    .unwrap();
+                history.push(next);
+                history
+            });
+        assert_eq!(history[0].clone().vec(), (0..10).collect::<Vec<u64>>());
+        assert_eq!(
+            history[1].clone().vec(),
+            Deck::new(10).cut(1).deal_with_increment(3).unwrap().vec()
+        );
+        assert_ne!(history[4], history[5]);
+    }
+}
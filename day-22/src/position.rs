@@ -0,0 +1,120 @@
+// Following a single card through a shuffle, one instruction at a time,
+// without laying out the rest of the deck.
+
+use crate::affine::{mod_inverse, reduce};
+use crate::instruction::Instruction;
+
+// where the card at `position` in a deck of `n` is after `instruction`
+pub fn forward(instruction: &Instruction, position: u64, n: u64) -> u64 {
+    let position = u128::from(position % n);
+    let n128 = u128::from(n);
+    let moved = match *instruction {
+        Instruction::Cut(k) => position + n128 - u128::from(reduce(i64::from(k), n)),
+        Instruction::DealIntoNewStack => n128 - 1 - position,
+        Instruction::DealWithIncrement(k) => position * u128::from(reduce(i64::from(k), n)),
+    };
+    (moved % n128) as u64
+}
+
+// where the card now at `position` was before `instruction`; `None` when
+// dealing with an increment that doesn't visit every position
+pub fn backward(instruction: &Instruction, position: u64, n: u64) -> Option<u64> {
+    let position = u128::from(position % n);
+    let n128 = u128::from(n);
+    let moved = match *instruction {
+        Instruction::Cut(k) => position + u128::from(reduce(i64::from(k), n)),
+        Instruction::DealIntoNewStack => n128 - 1 - position,
+        Instruction::DealWithIncrement(k) => {
+            position * u128::from(mod_inverse(reduce(i64::from(k), n), n)?)
+        }
+    };
+    Some((moved % n128) as u64)
+}
+
+pub fn forward_all(instructions: &[Instruction], position: u64, n: u64) -> u64 {
+    instructions.iter().fold(position, |p, i| forward(i, p, n))
+}
+
+pub fn backward_all(instructions: &[Instruction], position: u64, n: u64) -> Option<u64> {
+    instructions
+        .iter()
+        .rev()
+        .try_fold(position, |p, i| backward(i, p, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::Deck;
+    use crate::examples::{self, DECK_SIZE};
+
+    const PRIMES: [i32; 6] = [2, 3, 5, 7, 11, 13];
+
+    fn instructions(n: i32) -> Vec<Instruction> {
+        let mut all = vec![Instruction::DealIntoNewStack];
+        for k in -n + 1..n {
+            all.push(Instruction::Cut(k));
+            if k > 0 && k % n != 0 {
+                all.push(Instruction::DealWithIncrement(k));
+            }
+        }
+        all
+    }
+
+    // every single instruction, on every small prime deck, against the deck
+    #[test]
+    fn test_single_instructions_match_deck() {
+        for &n in PRIMES.iter() {
+            for instruction in instructions(n) {
+                let cards = Deck::new(n as u64).apply(&instruction).unwrap().vec();
+                for (position, &card) in cards.iter().enumerate() {
+                    let position = position as u64;
+                    assert_eq!(forward(&instruction, card, n as u64), position);
+                    assert_eq!(backward(&instruction, position, n as u64), Some(card));
+                }
+            }
+        }
+    }
+
+    // a walk through pseudo-random sequences of instructions
+    #[test]
+    fn test_sequences_match_deck() {
+        let mut state: u64 = 2019;
+        let mut next = |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % bound as u64) as usize
+        };
+        for _ in 0..200 {
+            let n = PRIMES[next(PRIMES.len())];
+            let choices = instructions(n);
+            let sequence: Vec<Instruction> = (0..next(12))
+                .map(|_| choices[next(choices.len())])
+                .collect();
+            let cards = Deck::new(n as u64).shuffle(&sequence).unwrap().vec();
+            for (position, &card) in cards.iter().enumerate() {
+                let position = position as u64;
+                assert_eq!(forward_all(&sequence, card, n as u64), position);
+                assert_eq!(backward_all(&sequence, position, n as u64), Some(card));
+            }
+        }
+    }
+
+    #[test]
+    fn test_examples() {
+        for (instructions, cards) in examples::all() {
+            for (position, &card) in cards.iter().enumerate() {
+                let position = position as u64;
+                assert_eq!(forward_all(&instructions, card, DECK_SIZE), position);
+                assert_eq!(backward_all(&instructions, position, DECK_SIZE), Some(card));
+            }
+        }
+    }
+
+    #[test]
+    fn test_backward_needs_coprime_increment() {
+        assert_eq!(backward(&Instruction::DealWithIncrement(4), 2, 10), None);
+        assert_eq!(forward(&Instruction::Cut(-13), 0, 10), 3);
+    }
+}
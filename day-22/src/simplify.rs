@@ -0,0 +1,137 @@
+// Any list of instructions is one affine map for a given deck size, and any
+// invertible map can be written back out as at most three instructions:
+//
+//     deal into new stack      (only if it makes the increment smaller)
+//     deal with increment A
+//     cut C
+
+use std::fmt;
+
+use crate::affine::Affine;
+use crate::deck::{Deck, ShuffleError};
+use crate::instruction::Instruction;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SimplifyError {
+    // the instructions deal with an increment sharing a factor with the size
+    NotAPermutation,
+    // an argument that doesn't fit in an instruction
+    TooLarge(u64),
+}
+
+impl fmt::Display for SimplifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimplifyError::NotAPermutation => write!(f, "the shuffle loses cards"),
+            SimplifyError::TooLarge(v) => write!(f, "{} is too large for an instruction", v),
+        }
+    }
+}
+
+fn argument(value: u64) -> Result<i32, SimplifyError> {
+    if value > i32::MAX as u64 {
+        Err(SimplifyError::TooLarge(value))
+    } else {
+        Ok(value as i32)
+    }
+}
+
+impl Affine {
+    pub fn to_instructions(&self) -> Result<Vec<Instruction>, SimplifyError> {
+        let n = self.n;
+        if n <= 1 {
+            return Ok(Vec::new());
+        }
+        if self.inverse().is_none() {
+            return Err(SimplifyError::NotAPermutation);
+        }
+        // new stack, increment m, cut c is x -> -m*x - m - c, so m = -a and
+        // c = a - b
+        let reverse = n - self.a < self.a;
+        let (increment, cut) = if reverse {
+            (n - self.a, (self.a + n - self.b) % n)
+        } else {
+            (self.a, (n - self.b) % n)
+        };
+
+        let mut instructions = Vec::new();
+        if reverse {
+            instructions.push(Instruction::DealIntoNewStack);
+        }
+        if increment != 1 {
+            instructions.push(Instruction::DealWithIncrement(argument(increment)?));
+        }
+        if cut != 0 {
+            instructions.push(Instruction::Cut(argument(cut)?));
+        }
+        Ok(instructions)
+    }
+}
+
+pub fn simplify(instructions: &[Instruction], n: u64) -> Result<Vec<Instruction>, SimplifyError> {
+    Affine::from_instructions(instructions, n).to_instructions()
+}
+
+// in the same format as the puzzle input
+pub fn format_list(instructions: &[Instruction]) -> String {
+    instructions.iter().map(|i| format!("{}\n", i)).collect()
+}
+
+// whether both lists leave a deck of `n` cards in the same order
+pub fn equivalent(a: &[Instruction], b: &[Instruction], n: u64) -> Result<bool, ShuffleError> {
+    Ok(Deck::new(n).shuffle(a)? == Deck::new(n).shuffle(b)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_simplify_input() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("input.txt");
+        let instructions = Instruction::load(path).unwrap();
+        for &n in [7, 10, 11, 101, 10007].iter() {
+            if Deck::new(n as u64).shuffle(&instructions).is_err() {
+                continue;
+            }
+            let simplified = simplify(&instructions, n as u64).unwrap();
+            assert!(simplified.len() <= 3);
+            assert_eq!(equivalent(&instructions, &simplified, n as u64), Ok(true));
+            assert_eq!(
+                Instruction::parse_list(&format_list(&simplified)).unwrap(),
+                simplified
+            );
+        }
+    }
+
+    #[test]
+    fn test_simplify_small_cases() {
+        use Instruction::*;
+        assert_eq!(simplify(&[], 10), Ok(vec![]));
+        assert_eq!(
+            simplify(&[DealIntoNewStack, DealIntoNewStack], 10),
+            Ok(vec![])
+        );
+        assert_eq!(simplify(&[Cut(3), Cut(-5)], 10), Ok(vec![Cut(8)]));
+        assert_eq!(
+            simplify(&[DealIntoNewStack], 10),
+            Ok(vec![DealIntoNewStack])
+        );
+        for n in 2..30 {
+            for k in 1..n {
+                let list = [Cut(k), DealWithIncrement(k), DealIntoNewStack, Cut(-k)];
+                match simplify(&list, n as u64) {
+                    Ok(simplified) => {
+                        assert_eq!(equivalent(&list, &simplified, n as u64), Ok(true))
+                    }
+                    Err(e) => assert_eq!(e, SimplifyError::NotAPermutation),
+                }
+            }
+        }
+        assert_eq!(
+            simplify(&[DealWithIncrement(4)], 10),
+            Err(SimplifyError::NotAPermutation)
+        );
+    }
+}
@@ -0,0 +1,17 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use aoc_2019::intcode::fuzz::{self, Bytes};
+use aoc_2019::intcode::Interpreter;
+
+// the long-running counterpart to the seeded test in src/intcode/fuzz.rs:
+// cargo +nightly fuzz run intcode_differential
+fuzz_target!(|data: &[u8]| {
+    let case = fuzz::generate(&mut Bytes::new(data));
+    if let Err(mismatch) = fuzz::check::<Interpreter, Interpreter>(&case) {
+        panic!(
+            "\n{}",
+            fuzz::minimize::<Interpreter, Interpreter>(&mismatch)
+        );
+    }
+});
@@ -1,56 +1,116 @@
-use std::fs;
-use std::io;
-use std::io::BufRead;
+use std::env;
+use std::process;
 use std::str::FromStr;
 
-fn main() -> Result<(), std::io::Error> {
-    let f = fs::File::open("data/day1.txt")?;
-    let modules: Vec<i64> = io::BufReader::new(f)
-        .lines()
-        .map(Result::unwrap)
-        .map(|s| i64::from_str(&s))
-        .map(Result::unwrap)
-        .collect();
-    let sum: i64 = modules.iter().cloned().map(fuel_for).sum();
-    let sum_with_tyranny: i64 = modules.iter().cloned().map(fuel_for_fuel).sum();
-    println!("My fuel requirement is {}", sum);
-    println!(
-        "With the tyranny of the rocket equation, that's {}",
-        sum_with_tyranny
-    );
-    Ok(())
-}
+use aoc_2019::fuel;
+use aoc_2019::fuel::model::{self, FuelModel};
+use aoc_2019::fuel::report::{Column, Format, Report};
+
+const USAGE: &str = "usage: day1 [FILE] [options]
+
+  --report              list every module's mass, fuel and total fuel
+  --format FORMAT       print the report as a `table` (the default), `csv`
+                        or `json`
+  --sort COLUMN         order the report by `mass`, `fuel` or `total-fuel`,
+                        largest first
+  --at-least N          only report modules whose total fuel is at least N
+  --model MODEL         print the fleet's fuel under MODEL, which may be
+                        repeated to compare them: `linear:DIVISOR:OFFSET`
+                        or `converging:DIVISOR:OFFSET:TOLERANCE`
 
-fn fuel_for(mass: i64) -> i64 {
-    let fuel = (mass / 3) - 2;
-    fuel
+FILE defaults to data/day1.txt.";
+
+struct Options {
+    path: String,
+    report: bool,
+    format: Format,
+    sort: Option<Column>,
+    at_least: Option<u64>,
+    models: Vec<Box<dyn FuelModel>>,
 }
 
-fn fuel_for_fuel(mass: i64) -> i64 {
-    let fuel = fuel_for(mass);
-    if fuel > 0 {
-        fuel + fuel_for_fuel(fuel)
-    } else {
-        0
+fn parse_args(mut args: env::Args) -> Result<Options, String> {
+    args.next();
+    let mut options = Options {
+        path: "data/day1.txt".to_string(),
+        report: false,
+        format: Format::Table,
+        sort: None,
+        at_least: None,
+        models: Vec::new(),
+    };
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+        match arg.as_str() {
+            "--report" => options.report = true,
+            "--format" => options.format = Format::from_str(&value("--format")?)?,
+            "--sort" => options.sort = Some(Column::from_str(&value("--sort")?)?),
+            "--at-least" => {
+                let threshold = value("--at-least")?;
+                let threshold = u64::from_str(&threshold)
+                    .map_err(|_| format!("--at-least needs a number, not {:?}", threshold))?;
+                options.at_least = Some(threshold);
+            }
+            "--model" => options.models.push(model::parse(&value("--model")?)?),
+            _ if arg.starts_with("--") || path.is_some() => {
+                return Err(format!("unexpected argument {}", arg))
+            }
+            _ => path = Some(arg),
+        }
+    }
+    if let Some(path) = path {
+        options.path = path;
     }
+    Ok(options)
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+fn main() {
+    let options = parse_args(env::args()).unwrap_or_else(|e| {
+        eprintln!("day1: {}", e);
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    });
+    let modules = fuel::load(&options.path).unwrap_or_else(|e| {
+        eprintln!("day1: {}: {}", options.path, e);
+        process::exit(1);
+    });
 
-    #[test]
-    fn test_fuel_examples() {
-        assert_eq!(fuel_for(12), 2);
-        assert_eq!(fuel_for(14), 2);
-        assert_eq!(fuel_for(1969), 654);
-        assert_eq!(fuel_for(100756), 33583);
+    if !options.models.is_empty() {
+        for model in options.models.iter() {
+            match model.fleet_total(&modules) {
+                Some(total) => println!("{:<24} {}", model.describe(), total),
+                None => println!("{:<24} more than {}", model.describe(), u64::MAX),
+            }
+        }
+        return;
     }
 
-    #[test]
-    fn test_fuel_for_fuel_examples() {
-        assert_eq!(fuel_for_fuel(14), 2);
-        assert_eq!(fuel_for_fuel(1969), 966);
-        assert_eq!(fuel_for_fuel(100756), 50346);
+    let mut report = Report::new(&modules);
+    if options.report {
+        if let Some(column) = options.sort {
+            report.sort_by(column);
+        }
+        if let Some(threshold) = options.at_least {
+            report.at_least(Column::TotalFuel, threshold);
+        }
+        print!("{}", report.format(options.format));
+        return;
+    }
+    match (report.fuel(), report.total_fuel()) {
+        (Some(fuel), Some(total_fuel)) => {
+            println!("My fuel requirement is {}", fuel);
+            println!(
+                "With the tyranny of the rocket equation, that's {}",
+                total_fuel
+            );
+        }
+        _ => {
+            eprintln!(
+                "day1: {}: the fleet needs more fuel than a u64 holds",
+                options.path
+            );
+            process::exit(1);
+        }
     }
 }
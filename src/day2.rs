@@ -1,35 +1,27 @@
-use std::fs;
-use std::io;
-use std::io::BufRead;
-use std::str::FromStr;
+use std::process;
+
+use aoc_2019::intcode;
+use aoc_2019::intcode::patch::Patch;
+use aoc_2019::intcode::{Interpreter, Machine};
 
 fn main() -> Result<(), std::io::Error> {
-    let f = fs::File::open("data/day2.txt")?;
-    let reader = io::BufReader::new(f);
-    let original_program: Vec<usize> = reader
-        .split(b',')
-        .map(Result::unwrap)
-        .map(|v| String::from_utf8(v))
-        .map(Result::unwrap)
-        .map(|s| usize::from_str((&s).trim()))
-        .map(Result::unwrap)
-        .collect();
+    // one machine for every run, reset in between rather than rebuilt
+    let mut machine = Interpreter::new(intcode::load("data/day2.txt")?);
 
     // before starting, fiddle the numbers
-    let mut program = original_program.clone();
-    program[1] = 12;
-    program[2] = 2;
-    let output = run(program);
+    let alarm = Patch::load("data/day2.patch").unwrap_or_else(|e| {
+        eprintln!("day2: data/day2.patch: {}", e);
+        process::exit(1);
+    });
+    let output = run_with(&mut machine, &alarm);
 
-    println!("Position 0: {}", output[0]);
+    println!("Position 0: {}", output);
 
+    let mut inputs = Patch::new();
     for i in 0..99 {
         for j in 0..99 {
-            let mut program = original_program.clone();
-            program[1] = i;
-            program[2] = j;
-            let output = run(program);
-            if output[0] == 19690720 {
+            let output = run_with(&mut machine, inputs.set(1, i).set(2, j));
+            if output == 19690720 {
                 println!("The inputs {} and {} produce the desired output.", i, j);
                 println!("The answer is: {}", 100 * i + j);
             }
@@ -38,32 +30,9 @@ fn main() -> Result<(), std::io::Error> {
     Ok(())
 }
 
-fn run(mut memory: Vec<usize>) -> Vec<usize> {
-    let mut i = 0; // the instruction pointer
-    loop {
-        match memory[i] {
-            1 => {
-                let a = memory[i + 1];
-                let b = memory[i + 2];
-                let c = memory[i + 3];
-                memory[c] = memory[a] + memory[b];
-                i = i + 4;
-            }
-            2 => {
-                let a = memory[i + 1];
-                let b = memory[i + 2];
-                let c = memory[i + 3];
-                memory[c] = memory[a] * memory[b];
-                i = i + 4;
-            }
-            99 => {
-                break;
-            }
-            _ => {
-                println!("Exception!");
-                break;
-            }
-        }
-    }
-    memory
+fn run_with(machine: &mut Interpreter, patch: &Patch) -> i32 {
+    machine.reset();
+    patch.apply(machine.memory_mut()).unwrap();
+    machine.run();
+    machine.memory()[0]
 }
@@ -1,162 +1,133 @@
-use std::collections::hash_set::HashSet;
-use std::fs;
-use std::io;
-use std::io::BufRead;
-use std::num::ParseIntError;
+use std::env;
+use std::process;
 use std::str::FromStr;
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-struct Point {
-    x: i32,
-    y: i32,
-}
+use aoc_2019::wires::render::{self, Format};
+use aoc_2019::wires::{self, Wire};
 
-impl Point {
-    fn manhattan(&self) -> i32 {
-        return self.x.abs() + self.y.abs();
-    }
-}
+const USAGE: &str = "usage: day3 [FILE] [options]
 
-#[derive(Clone, Debug)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
+  --at-least K          list every point where at least K of the wires in
+                        FILE cross, rather than answering for the first two
+  --render FORMAT       draw the wires instead, as `svg` or as the `ascii`
+                        grid from the puzzle, which only suits small inputs
 
-impl Direction {
-    fn step(&self, p: Point) -> Point {
-        match self {
-            Self::Up => Point { y: p.y + 1, ..p },
-            Self::Down => Point { y: p.y - 1, ..p },
-            Self::Left => Point { x: p.x - 1, ..p },
-            Self::Right => Point { x: p.x + 1, ..p },
-        }
-    }
-}
+FILE defaults to data/day3.txt.";
 
-#[derive(Clone, Debug)]
-struct Instruction {
-    direction: Direction,
-    distance: i32,
+struct Options {
+    path: String,
+    at_least: Option<usize>,
+    render: Option<Format>,
 }
 
-impl Instruction {
-    fn step(&self, start: Point) -> Vec<Point> {
-        let mut steps: Vec<Point> = Vec::new();
-        let mut next = start.clone();
-        for _ in 0..self.distance {
-            next = self.direction.step(next);
-            steps.push(next.clone());
+fn parse_args(mut args: env::Args) -> Result<Options, String> {
+    args.next();
+    let mut options = Options {
+        path: "data/day3.txt".to_string(),
+        at_least: None,
+        render: None,
+    };
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+        match arg.as_str() {
+            "--at-least" => {
+                let k = value("--at-least")?;
+                let k = usize::from_str(&k)
+                    .ok()
+                    .filter(|&k| k >= 2)
+                    .ok_or(format!("--at-least needs a number from 2 up, not {:?}", k))?;
+                options.at_least = Some(k);
+            }
+            "--render" => options.render = Some(Format::from_str(&value("--render")?)?),
+            _ if arg.starts_with("--") || path.is_some() => {
+                return Err(format!("unexpected argument {}", arg))
+            }
+            _ => path = Some(arg),
         }
-        steps
     }
-}
-
-#[derive(Debug)]
-enum InstructionParseError {
-    IntError(ParseIntError),
-    DirectionError,
-}
-
-impl From<ParseIntError> for InstructionParseError {
-    fn from(e: ParseIntError) -> Self {
-        InstructionParseError::IntError(e)
+    if let Some(path) = path {
+        options.path = path;
     }
+    Ok(options)
 }
 
-impl FromStr for Instruction {
-    type Err = InstructionParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let direction = match s.as_bytes()[0] {
-            b'U' => Ok(Direction::Up),
-            b'D' => Ok(Direction::Down),
-            b'L' => Ok(Direction::Left),
-            b'R' => Ok(Direction::Right),
-            _ => Err(InstructionParseError::DirectionError),
-        }?;
-        let distance = i32::from_str(&s[1..])?;
-        Ok(Instruction {
-            direction,
-            distance,
-        })
+fn main() {
+    let options = parse_args(env::args()).unwrap_or_else(|e| {
+        eprintln!("day3: {}", e);
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    });
+    let instructions = wires::load(&options.path).unwrap_or_else(|e| {
+        eprintln!("day3: {}: {}", options.path, e);
+        process::exit(1);
+    });
+
+    match options.render {
+        Some(Format::Svg) => {
+            print!("{}", render::svg(&instructions));
+            return;
+        }
+        Some(Format::Ascii) => {
+            let grid = render::ascii(&instructions).unwrap_or_else(|| {
+                eprintln!(
+                    "day3: {}: too big to draw as ascii, which is limited to {} cells a side",
+                    options.path,
+                    render::ASCII_MAX_SIDE
+                );
+                process::exit(1);
+            });
+            print!("{}", grid);
+            return;
+        }
+        None => {}
     }
-}
-
-fn main() -> Result<(), std::io::Error> {
-    let f = fs::File::open("data/day3.txt")?;
-    let reader = io::BufReader::new(f);
-    let instructions: Vec<Vec<Instruction>> = reader
-        .lines()
-        .map(Result::unwrap)
-        .map(|l| {
-            let instructions = l
-                .split(',')
-                .map(Instruction::from_str)
-                .map(Result::unwrap)
-                .collect();
-            instructions
-        })
-        .collect();
 
-    // make hash sets
-    let first = visit(instructions.get(0).unwrap().clone());
-    let first_set: HashSet<Point> = first.iter().cloned().collect();
-    let second = visit(instructions.get(1).unwrap().clone());
-    let second_set: HashSet<Point> = second.iter().cloned().collect();
-    println!("path lengths: {}, {}", first.len(), second.len());
+    let all: Vec<Wire> = instructions.iter().map(|w| Wire::new(w)).collect();
 
-    let mut shared: Vec<Point> = first_set.intersection(&second_set).cloned().collect();
-    {
-        // if it loops back around to the origin (0, 0) might appear again
-        let origin = shared.iter().position(|i| i == &Point { x: 0, y: 0 });
-        if origin.is_some() {
-            shared.remove(origin.unwrap());
+    if let Some(k) = options.at_least {
+        for junction in wires::junctions(&all, k) {
+            let wires: Vec<String> = junction
+                .wires
+                .iter()
+                .map(|(wire, steps)| format!("wire {} after {} steps", wire, steps))
+                .collect();
+            println!(
+                "{:?} (distance {}): {}",
+                junction.point,
+                junction.manhattan(),
+                wires.join(", ")
+            );
         }
-    };
-    shared.sort_by(|p, q| (p.manhattan()).cmp(&q.manhattan()));
-    println!(
-        "First and last: {:?} (distance {}), {:?} (distance {})",
-        shared.get(1),
-        shared.get(1).unwrap().manhattan(),
-        shared.last(),
-        shared.last().unwrap().manhattan()
-    );
-
-    // work out
-    let mut closest_connection = shared
-        .iter()
-        .map(|p| {
-            (
-                p,
-                first.iter().position(|i| i == p).unwrap()
-                    + second.iter().position(|i| i == p).unwrap(),
-            )
-        })
-        .collect::<Vec<(&Point, usize)>>();
-    closest_connection.sort_by(|p, q| p.1.cmp(&q.1));
-    println!(
-        "closest connection: {:?}, furthest: {:?}, all: {:?}",
-        closest_connection.first(),
-        closest_connection.last(),
-        closest_connection
-    );
-
-    Ok(())
-}
+        return;
+    }
 
-fn visit(instructions: Vec<Instruction>) -> Vec<Point> {
-    let mut points_visited: Vec<Point> = Vec::new();
-    points_visited.push(Point { x: 0, y: 0 });
-    let all_points =
-        instructions
-            .iter()
-            .fold(points_visited, |mut a: Vec<Point>, i: &Instruction| {
-                let new_points = i.step(a.last().unwrap().clone());
-                a.extend(new_points);
-                a
-            });
-    all_points
+    // the puzzle is about two wires; any more are only looked at by
+    // --at-least
+    if all.len() < 2 {
+        eprintln!(
+            "day3: {}: expected two wires, found {}",
+            options.path,
+            all.len()
+        );
+        process::exit(1);
+    }
+    if all.len() > 2 {
+        eprintln!(
+            "day3: {}: only using the first two of {} wires; see --at-least",
+            options.path,
+            all.len()
+        );
+    }
+    let pair = &all[..2];
+    println!("path lengths: {}, {}", pair[0].length(), pair[1].length());
+    match wires::closest_by_manhattan(pair) {
+        Some((point, distance)) => {
+            println!("closest crossing: {:?} (distance {})", point, distance)
+        }
+        None => println!("the wires never cross"),
+    }
+    if let Some((point, steps)) = wires::closest_by_steps(pair) {
+        println!("closest connection: {:?} ({} steps)", point, steps);
+    }
 }
@@ -1,176 +1,108 @@
-use std::convert::From;
-use std::ops::Deref;
+use std::env;
+use std::process;
+use std::str::FromStr;
 
-#[derive(Clone, Eq, PartialEq)]
-struct Password(Vec<i32>);
+use aoc_2019::password::{Password, RuleSet, PART_ONE, PART_TWO};
 
-impl From<i32> for Password {
-    fn from(item: i32) -> Self {
-        let mut digits: Vec<i32> = Vec::new();
-        let mut n = item;
-        while n > 9 {
-            digits.push(n % 10);
-            n = n / 10;
-        }
-        digits.push(n);
-        digits.reverse();
-        Password(digits)
-    }
-}
-
-#[test]
-fn test_password_from() {
-    assert_eq!(Password::from(1234).0, vec![1i32, 2, 3, 4]);
-}
-
-impl Deref for Password {
-    type Target = Vec<i32>;
-
-    fn deref(&self) -> &Self::Target {
-        return &self.0;
-    }
-}
-
-trait PasswordRule {
-    fn validate(&self, input: &Password) -> bool;
-}
+const USAGE: &str = "usage: day4 [LOW HIGH] [options]
 
-struct NumDigits(usize);
+  --rules SPEC          check against SPEC rather than the puzzle's two rule
+                        sets, e.g. `digits=6; nondecreasing; group_exact=2`
+  --spec FILE           read the rules from FILE, as SPEC or as `name = value`
+                        lines under an optional [table] header
+  --verbose             list every candidate, with the rules that reject it
 
-impl PasswordRule for NumDigits {
-    fn validate(&self, input: &Password) -> bool {
-        input.0.len() == self.0.into()
-    }
-}
+LOW and HIGH are both included, and default to the puzzle's 357253 and
+892942.";
 
-#[test]
-fn test_sixdigits() {
-    let six_digits = NumDigits(6);
-    assert!(six_digits.validate(&123456.into()));
-    assert!(!six_digits.validate(&12345.into()));
-    assert!(!six_digits.validate(&1234567891.into()));
-    assert!(NumDigits(10).validate(&1234567891.into()));
+struct Options {
+    low: i32,
+    high: i32,
+    // a name for each rule set, and the set
+    rules: Vec<(String, RuleSet)>,
+    verbose: bool,
 }
 
-struct TwoAdjacentIdenticalDigits;
-
-impl PasswordRule for TwoAdjacentIdenticalDigits {
-    fn validate(&self, input: &Password) -> bool {
-        let iter_a = input.iter();
-        let mut iter_b = input.iter();
-        iter_b.next();
-        for (a, b) in iter_a.zip(iter_b) {
-            if a == b {
-                return true;
+fn parse_args(mut args: env::Args) -> Result<Options, String> {
+    args.next();
+    let mut options = Options {
+        low: 357253,
+        high: 892942,
+        rules: Vec::new(),
+        verbose: false,
+    };
+    let mut range = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+        match arg.as_str() {
+            "--rules" => {
+                let rules = RuleSet::from_str(&value("--rules")?).map_err(|e| e.to_string())?;
+                options.rules.push(("rules".to_string(), rules));
             }
-        }
-        false
-    }
-}
-
-#[test]
-fn test_two_adjacent() {
-    assert!(TwoAdjacentIdenticalDigits {}.validate(&1223.into()));
-    assert!(!TwoAdjacentIdenticalDigits {}.validate(&1234.into()));
-}
-
-struct AlwaysIncreasing;
-
-impl PasswordRule for AlwaysIncreasing {
-    fn validate(&self, input: &Password) -> bool {
-        let iter_a = input.iter();
-        let mut iter_b = input.iter();
-        iter_b.next();
-        for (a, b) in iter_a.zip(iter_b) {
-            if b < a {
-                return false;
+            "--spec" => {
+                let path = value("--spec")?;
+                let rules = RuleSet::load(&path).map_err(|e| format!("{}: {}", path, e))?;
+                options.rules.push(("rules".to_string(), rules));
+            }
+            "--verbose" => options.verbose = true,
+            _ if arg.starts_with("--") || range.len() == 2 => {
+                return Err(format!("unexpected argument {}", arg))
             }
+            _ => range.push(
+                i32::from_str(&arg)
+                    .ok()
+                    .filter(|&n| n >= 0)
+                    .ok_or(format!("expected a password, not {:?}", arg))?,
+            ),
         }
-        true
     }
-}
-
-#[test]
-fn test_always_increasing() {
-    let rule = AlwaysIncreasing {};
-    assert!(rule.validate(&1234.into()));
-    assert!(!rule.validate(&4321.into()));
-    assert!(rule.validate(&1233.into()));
-    assert!(!rule.validate(&1232.into()));
-}
-
-struct AtLeastOneGroupOfLength(usize);
-
-impl AtLeastOneGroupOfLength {
-    fn groups(input: &Password) -> Vec<Vec<i32>> {
-        let mut groups: Vec<Vec<i32>> = Vec::new();
-        let mut group: Vec<i32> = Vec::new();
-        for a in input.iter() {
-            if !group.is_empty() && a != group.last().unwrap() {
-                groups.push(group);
-                group = Vec::new();
-            }
-            group.push(*a);
+    match range[..] {
+        [] => {}
+        [low, high] if low <= high => {
+            options.low = low;
+            options.high = high;
         }
-        groups.push(group);
-        groups
+        [_, _] => return Err("LOW has to be no more than HIGH".to_string()),
+        _ => return Err("expected both LOW and HIGH".to_string()),
     }
-}
-
-#[test]
-fn test_groups() {
-    assert_eq!(
-        AtLeastOneGroupOfLength::groups(&1234.into()),
-        vec![vec![1i32], vec![2], vec![3], vec![4]]
-    );
-}
-
-impl PasswordRule for AtLeastOneGroupOfLength {
-    fn validate(&self, input: &Password) -> bool {
-        let groups = AtLeastOneGroupOfLength::groups(input);
-        groups.iter().any(|g| g.len() == self.0)
+    if options.rules.len() > 1 {
+        return Err("only one of --rules and --spec".to_string());
     }
-}
-
-#[test]
-fn test_at_least_one_group_of_length() {
-    let two = AtLeastOneGroupOfLength(2);
-    let five = AtLeastOneGroupOfLength(5);
-
-    assert!(two.validate(&1223.into()));
-    assert!(!two.validate(&1234.into()));
-    assert!(two.validate(&112233.into()));
-    assert!(!two.validate(&123444.into()));
-    assert!(two.validate(&111122.into()));
-
-    assert!(five.validate(&1222223.into()));
-    assert!(!five.validate(&12222223.into()));
+    if options.rules.is_empty() {
+        for &(name, spec) in [("initial rules", PART_ONE), ("new rules", PART_TWO)].iter() {
+            options
+                .rules
+                .push((name.to_string(), RuleSet::from_str(spec).unwrap()));
+        }
+    }
+    Ok(options)
 }
 
 fn main() {
-    let rules: Vec<Box<dyn PasswordRule>> = vec![
-        Box::new(TwoAdjacentIdenticalDigits {}),
-        Box::new(NumDigits(6)),
-        Box::new(AlwaysIncreasing {}),
-    ];
-    println!(
-        "passwords matching initial rules: {}",
-        (357253..892942)
-            .map(Password::from)
-            .filter(|v| rules.iter().all(|r| r.validate(v)))
-            .count()
-    );
-
-    let part_two_rules: Vec<Box<dyn PasswordRule>> = vec![
-        Box::new(NumDigits(6)),
-        Box::new(AlwaysIncreasing {}),
-        Box::new(AtLeastOneGroupOfLength(2)),
-    ];
-    println!(
-        "passwords matching new rules: {}",
-        (357253..892942)
-            .map(Password::from)
-            .filter(|v| part_two_rules.iter().all(|r| r.validate(v)))
-            .count()
-    );
+    let options = parse_args(env::args()).unwrap_or_else(|e| {
+        eprintln!("day4: {}", e);
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    });
+
+    for (name, rules) in options.rules.iter() {
+        let mut count = 0;
+        if options.verbose {
+            println!("{}: {}", name, rules.describe());
+        }
+        for candidate in options.low..=options.high {
+            let rejections = rules.rejections(&Password::from(candidate));
+            if rejections.is_empty() {
+                count += 1;
+            }
+            if options.verbose {
+                let rejected: Vec<String> = rejections.iter().map(|r| r.describe()).collect();
+                match rejected[..] {
+                    [] => println!("  {}: ok", candidate),
+                    _ => println!("  {}: rejected by {}", candidate, rejected.join(", ")),
+                }
+            }
+        }
+        println!("passwords matching {}: {}", name, count);
+    }
 }
@@ -19,7 +19,7 @@ fn main() -> io::Result<()> {
     println!("y: {:?}, s: {:?}", you_chain.last(), san_chain.last());
     let mut counter = 0;
     for (y, s) in you_chain.iter().zip(san_chain.iter()) {
-        counter = counter + 1;
+        counter += 1;
         if y != s {
             println!(
                 "hops: {}",
@@ -47,14 +47,9 @@ fn count_orbits(orbits: Rc<HashMap<String, String>>) -> i32 {
     let mut counter = 0;
     for body in orbits.keys() {
         let mut secondary = body;
-        loop {
-            match orbits.get(secondary) {
-                Some(primary) => {
-                    secondary = primary;
-                    counter = counter + 1;
-                }
-                None => break,
-            }
+        while let Some(primary) = orbits.get(secondary) {
+            secondary = primary;
+            counter += 1;
         }
     }
     counter
@@ -63,14 +58,9 @@ fn count_orbits(orbits: Rc<HashMap<String, String>>) -> i32 {
 fn chain_for(orbits: Rc<HashMap<String, String>>, body: String) -> Vec<String> {
     let mut chain: Vec<String> = vec![body.clone()];
     let mut secondary = &body.clone();
-    loop {
-        match orbits.get(secondary) {
-            Some(primary) => {
-                chain.push(secondary.clone());
-                secondary = primary;
-            }
-            None => break,
-        }
+    while let Some(primary) = orbits.get(secondary) {
+        chain.push(secondary.clone());
+        secondary = primary;
     }
     chain
 }
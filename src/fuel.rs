@@ -0,0 +1,227 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+pub mod model;
+pub mod report;
+
+pub fn fuel_for(mass: i64) -> i64 {
+    (mass / 3) - 2
+}
+
+// light modules would otherwise come out as needing negative fuel
+pub fn clamped_fuel_for(mass: i64) -> i64 {
+    fuel_for(mass).max(0)
+}
+
+// the fuel for a module, plus the fuel to carry that fuel, and so on
+pub fn fuel_for_fuel(mass: i64) -> i64 {
+    let mut total = 0;
+    let mut fuel = clamped_fuel_for(mass);
+    while fuel > 0 {
+        total += fuel;
+        fuel = clamped_fuel_for(fuel);
+    }
+    total
+}
+
+// unsigned masses, where running out of room is an error rather than a wrap
+pub trait Mass: Copy + Ord {
+    const ZERO: Self;
+
+    // never below zero
+    fn fuel(self) -> Self;
+
+    // the same equation with another divisor and offset, which has to be
+    // more than zero; also never below zero
+    fn fuel_by(self, divisor: Self, offset: Self) -> Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_mass {
+    ($t:ty) => {
+        impl Mass for $t {
+            const ZERO: $t = 0;
+
+            fn fuel(self) -> $t {
+                self.fuel_by(3, 2)
+            }
+
+            fn fuel_by(self, divisor: $t, offset: $t) -> $t {
+                (self / divisor).saturating_sub(offset)
+            }
+
+            fn checked_add(self, other: $t) -> Option<$t> {
+                <$t>::checked_add(self, other)
+            }
+        }
+    };
+}
+
+impl_mass!(u64);
+impl_mass!(u128);
+
+// `None` for a module too light to need any fuel
+pub fn checked_fuel<M: Mass>(mass: M) -> Option<M> {
+    match mass.fuel() {
+        fuel if fuel == M::ZERO => None,
+        fuel => Some(fuel),
+    }
+}
+
+// every step is under a third of the one before, so the total of a single
+// module is always less than its mass and can't overflow
+pub fn total_fuel<M: Mass>(mass: M) -> M {
+    let mut total = M::ZERO;
+    let mut mass = mass;
+    while let Some(fuel) = checked_fuel(mass) {
+        total = total.checked_add(fuel).unwrap();
+        mass = fuel;
+    }
+    total
+}
+
+// the fuel for each module in turn, written to `fuel`; a plain loop over
+// slices of the same length, which the compiler can vectorize
+pub fn fuel_into<M: Mass>(masses: &[M], fuel: &mut [M]) {
+    assert_eq!(masses.len(), fuel.len());
+    for (mass, fuel) in masses.iter().zip(fuel.iter_mut()) {
+        *fuel = mass.fuel();
+    }
+}
+
+pub fn total_fuel_into<M: Mass>(masses: &[M], fuel: &mut [M]) {
+    assert_eq!(masses.len(), fuel.len());
+    for (&mass, fuel) in masses.iter().zip(fuel.iter_mut()) {
+        *fuel = total_fuel(mass);
+    }
+}
+
+// `None` if the fleet's fuel doesn't fit in `M`
+pub fn sum_fuel<M: Mass>(masses: &[M]) -> Option<M> {
+    masses
+        .iter()
+        .try_fold(M::ZERO, |sum, mass| sum.checked_add(mass.fuel()))
+}
+
+pub fn sum_total_fuel<M: Mass>(masses: &[M]) -> Option<M> {
+    masses
+        .iter()
+        .try_fold(M::ZERO, |sum, &mass| sum.checked_add(total_fuel(mass)))
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    // a line that isn't a mass, numbered from 1
+    Syntax(usize, String),
+}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "{}", e),
+            ParseError::Syntax(line, text) => {
+                write!(f, "line {}: expected a mass, got {:?}", line, text)
+            }
+        }
+    }
+}
+
+// one module mass per line, which can't be negative; blank lines are skipped
+pub fn parse_masses(s: &str) -> Result<Vec<u64>, ParseError> {
+    s.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            u64::from_str(line.trim()).map_err(|_| ParseError::Syntax(i + 1, line.trim().into()))
+        })
+        .collect()
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<u64>, ParseError> {
+    parse_masses(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuel_examples() {
+        assert_eq!(fuel_for(12), 2);
+        assert_eq!(fuel_for(14), 2);
+        assert_eq!(fuel_for(1969), 654);
+        assert_eq!(fuel_for(100756), 33583);
+    }
+
+    #[test]
+    fn test_fuel_for_fuel_examples() {
+        assert_eq!(fuel_for_fuel(14), 2);
+        assert_eq!(fuel_for_fuel(1969), 966);
+        assert_eq!(fuel_for_fuel(100756), 50346);
+    }
+
+    #[test]
+    fn test_light_modules() {
+        assert_eq!(fuel_for(5), -1);
+        assert_eq!(clamped_fuel_for(5), 0);
+        assert_eq!(clamped_fuel_for(-30), 0);
+        assert_eq!(fuel_for_fuel(5), 0);
+        assert_eq!(checked_fuel(8u64), None);
+        assert_eq!(checked_fuel(9u64), Some(1));
+    }
+
+    #[test]
+    fn test_unsigned_masses() {
+        for &mass in [0, 12, 14, 1969, 100756].iter() {
+            assert_eq!(total_fuel(mass as u64) as i64, fuel_for_fuel(mass));
+            assert_eq!(total_fuel(mass as u128) as i64, fuel_for_fuel(mass));
+        }
+        assert!(total_fuel(u64::MAX) < u64::MAX / 2);
+        assert!(total_fuel(u128::MAX) < u128::MAX / 2);
+    }
+
+    #[test]
+    fn test_batches() {
+        let masses: Vec<u64> = vec![12, 14, 1969, 100756];
+        let mut fuel = vec![0; masses.len()];
+        fuel_into(&masses, &mut fuel);
+        assert_eq!(fuel, vec![2, 2, 654, 33583]);
+        total_fuel_into(&masses, &mut fuel);
+        assert_eq!(fuel, vec![2, 2, 966, 50346]);
+        assert_eq!(sum_fuel(&masses), Some(2 + 2 + 654 + 33583));
+        assert_eq!(sum_total_fuel(&masses), Some(2 + 2 + 966 + 50346));
+
+        let heavy = vec![u64::MAX; 4];
+        assert_eq!(sum_fuel(&heavy), None);
+        let heavy: Vec<u128> = heavy.iter().map(|&m| u128::from(m)).collect();
+        assert!(sum_total_fuel(&heavy).is_some());
+    }
+
+    #[test]
+    fn test_parse_masses() {
+        assert_eq!(
+            parse_masses("12\n\n 14 \n1969\n").unwrap(),
+            vec![12, 14, 1969]
+        );
+        match parse_masses("12\n14\nheavy\n") {
+            Err(ParseError::Syntax(3, text)) => assert_eq!(text, "heavy"),
+            other => panic!("unexpected {:?}", other),
+        }
+        match parse_masses("12\n-14\n") {
+            Err(ParseError::Syntax(2, text)) => assert_eq!(text, "-14"),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(load("data/day1.txt").unwrap().len(), 100);
+    }
+}
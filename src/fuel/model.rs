@@ -0,0 +1,197 @@
+// Different rocket equations, so the same fleet of modules can be costed
+// more than one way.
+//
+// `linear:DIVISOR:OFFSET` is fuel of mass / DIVISOR - OFFSET, or none if
+// that's below zero.
+//
+// `converging:DIVISOR:OFFSET:TOLERANCE` is the same equation, also carrying
+// the fuel's own mass until each extra amount is within TOLERANCE.
+
+use std::str::FromStr;
+
+use super::Mass;
+
+// masses are unsigned, and the equations are those of `Mass`, so no model
+// ever asks for less than no fuel
+pub trait FuelModel {
+    // `None` if the fuel doesn't fit in a u64
+    fn fuel(&self, mass: u64) -> Option<u64>;
+
+    // in the same form `parse` takes
+    fn describe(&self) -> String;
+
+    fn fleet_total(&self, masses: &[u64]) -> Option<u64> {
+        masses
+            .iter()
+            .try_fold(0, |sum: u64, &mass| sum.checked_add(self.fuel(mass)?))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Linear {
+    pub divisor: u64,
+    pub offset: u64,
+}
+
+impl Linear {
+    pub fn new(divisor: u64, offset: u64) -> Linear {
+        assert!(divisor > 0, "fuel divisor must be positive");
+        Linear { divisor, offset }
+    }
+
+    fn step(&self, mass: u64) -> u64 {
+        mass.fuel_by(self.divisor, self.offset)
+    }
+}
+
+// the equation from part one
+impl Default for Linear {
+    fn default() -> Linear {
+        Linear::new(3, 2)
+    }
+}
+
+impl FuelModel for Linear {
+    fn fuel(&self, mass: u64) -> Option<u64> {
+        Some(self.step(mass))
+    }
+
+    fn describe(&self) -> String {
+        format!("linear:{}:{}", self.divisor, self.offset)
+    }
+}
+
+// adds fuel for the fuel until the next amount would be no more than
+// `tolerance`, or would stop getting smaller; the default is part two, which
+// carries on until no more fuel is needed
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Converging {
+    pub base: Linear,
+    pub tolerance: u64,
+}
+
+impl FuelModel for Converging {
+    fn fuel(&self, mass: u64) -> Option<u64> {
+        let mut total: u64 = 0;
+        let mut last = mass;
+        loop {
+            let fuel = self.base.step(last);
+            if fuel <= self.tolerance || fuel >= last {
+                return Some(total);
+            }
+            total = total.checked_add(fuel)?;
+            last = fuel;
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "converging:{}:{}:{}",
+            self.base.divisor, self.base.offset, self.tolerance
+        )
+    }
+}
+
+fn numbers(s: &str, count: usize) -> Option<Vec<u64>> {
+    let numbers: Vec<u64> = s
+        .split(':')
+        .map(u64::from_str)
+        .collect::<Result<_, _>>()
+        .ok()?;
+    if numbers.len() == count {
+        Some(numbers)
+    } else {
+        None
+    }
+}
+
+pub fn parse(s: &str) -> Result<Box<dyn FuelModel>, String> {
+    let bad = || format!("{:?} isn't a fuel model", s);
+    let (kind, rest) = match s.find(':') {
+        Some(colon) => (&s[..colon], &s[colon + 1..]),
+        None => (s, ""),
+    };
+    let model: Box<dyn FuelModel> = match kind {
+        "linear" => {
+            let n = numbers(rest, 2).ok_or_else(bad)?;
+            if n[0] == 0 {
+                return Err(bad());
+            }
+            Box::new(Linear::new(n[0], n[1]))
+        }
+        "converging" => {
+            let n = numbers(rest, 3).ok_or_else(bad)?;
+            if n[0] == 0 {
+                return Err(bad());
+            }
+            Box::new(Converging {
+                base: Linear::new(n[0], n[1]),
+                tolerance: n[2],
+            })
+        }
+        _ => return Err(bad()),
+    };
+    Ok(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{clamped_fuel_for, fuel_for_fuel};
+    use super::*;
+
+    #[test]
+    fn test_defaults_match_the_puzzle() {
+        for &mass in [0, 5, 12, 14, 1969, 100756].iter() {
+            let clamped = clamped_fuel_for(mass) as u64;
+            assert_eq!(Linear::default().fuel(mass as u64), Some(clamped));
+            let total = fuel_for_fuel(mass) as u64;
+            assert_eq!(Converging::default().fuel(mass as u64), Some(total));
+        }
+    }
+
+    #[test]
+    fn test_tolerance() {
+        let coarse = Converging {
+            base: Linear::default(),
+            tolerance: 100,
+        };
+        // 654 + 216, leaving off the 70 + 21 + 5
+        assert_eq!(coarse.fuel(1969), Some(870));
+        // a divisor of one never gets any smaller
+        let stuck = Converging {
+            base: Linear::new(1, 0),
+            tolerance: 0,
+        };
+        assert_eq!(stuck.fuel(100), Some(0));
+        // nor does an offset of one, but it adds up to more than a u64 holds
+        let endless = Converging {
+            base: Linear::new(1, 1),
+            tolerance: u64::MAX / 2,
+        };
+        assert_eq!(endless.fuel(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        let masses = [12, 1969, 100756];
+        let models: Vec<Box<dyn FuelModel>> = ["linear:3:2", "converging:3:2:0", "linear:4:0"]
+            .iter()
+            .map(|s| parse(s).unwrap())
+            .collect();
+        let totals: Vec<Option<u64>> = models.iter().map(|m| m.fleet_total(&masses)).collect();
+        assert_eq!(
+            totals,
+            vec![
+                Some(2 + 654 + 33583),
+                Some(2 + 966 + 50346),
+                Some(3 + 492 + 25189)
+            ]
+        );
+        assert_eq!(models[0].fleet_total(&[u64::MAX; 4]), None);
+        assert_eq!(models[1].describe(), "converging:3:2:0");
+        assert!(parse("linear:0:2").is_err());
+        assert!(parse("linear:3").is_err());
+        assert!(parse("linear:3:-2").is_err());
+        assert!(parse("quadratic:1:2").is_err());
+    }
+}
@@ -0,0 +1,230 @@
+// A per-module breakdown of the fuel needed, as a table, CSV or JSON, each
+// ending with the totals for the whole fleet. A total that doesn't fit in a
+// u64 is shown as `overflow`.
+
+use std::str::FromStr;
+
+use super::{total_fuel, Mass};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ModuleFuel {
+    pub mass: u64,
+    // just for the module itself
+    pub fuel: u64,
+    // including the fuel to carry the fuel
+    pub total_fuel: u64,
+}
+
+impl ModuleFuel {
+    pub fn new(mass: u64) -> ModuleFuel {
+        ModuleFuel {
+            mass,
+            fuel: mass.fuel(),
+            total_fuel: total_fuel(mass),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Column {
+    Mass,
+    Fuel,
+    TotalFuel,
+}
+
+impl Column {
+    fn of(self, module: &ModuleFuel) -> u64 {
+        match self {
+            Column::Mass => module.mass,
+            Column::Fuel => module.fuel,
+            Column::TotalFuel => module.total_fuel,
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mass" => Ok(Column::Mass),
+            "fuel" => Ok(Column::Fuel),
+            "total-fuel" => Ok(Column::TotalFuel),
+            _ => Err(format!("unknown column {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Report {
+    pub modules: Vec<ModuleFuel>,
+}
+
+impl Report {
+    pub fn new(masses: &[u64]) -> Report {
+        Report {
+            modules: masses.iter().map(|&mass| ModuleFuel::new(mass)).collect(),
+        }
+    }
+
+    // largest first; modules with equal values keep their input order
+    pub fn sort_by(&mut self, column: Column) -> &mut Report {
+        self.modules
+            .sort_by_key(|module| std::cmp::Reverse(column.of(module)));
+        self
+    }
+
+    // keep only the modules where `column` is at least `threshold`
+    pub fn at_least(&mut self, column: Column, threshold: u64) -> &mut Report {
+        self.modules.retain(|module| column.of(module) >= threshold);
+        self
+    }
+
+    // `None` if the sum doesn't fit in a u64
+    pub fn fuel(&self) -> Option<u64> {
+        self.sum(Column::Fuel)
+    }
+
+    pub fn total_fuel(&self) -> Option<u64> {
+        self.sum(Column::TotalFuel)
+    }
+
+    fn sum(&self, column: Column) -> Option<u64> {
+        self.modules
+            .iter()
+            .try_fold(0u64, |sum, m| sum.checked_add(column.of(m)))
+    }
+
+    // the totals as they're written out
+    fn totals(&self) -> (String, String) {
+        let show = |total: Option<u64>| total.map_or("overflow".to_string(), |t| t.to_string());
+        (show(self.fuel()), show(self.total_fuel()))
+    }
+
+    pub fn format(&self, format: Format) -> String {
+        match format {
+            Format::Table => self.table(),
+            Format::Csv => self.csv(),
+            Format::Json => self.json(),
+        }
+    }
+
+    fn table(&self) -> String {
+        let mut out = format!("{:>10} {:>10} {:>12}\n", "mass", "fuel", "total fuel");
+        for m in self.modules.iter() {
+            out += &format!("{:>10} {:>10} {:>12}\n", m.mass, m.fuel, m.total_fuel);
+        }
+        let (fuel, total_fuel) = self.totals();
+        out += &format!("{:>10} {:>10} {:>12}\n", "total", fuel, total_fuel);
+        out
+    }
+
+    fn csv(&self) -> String {
+        let mut out = "mass,fuel,total_fuel\n".to_string();
+        for m in self.modules.iter() {
+            out += &format!("{},{},{}\n", m.mass, m.fuel, m.total_fuel);
+        }
+        // as the table has, in the mass column so it still has three fields
+        let (fuel, total_fuel) = self.totals();
+        out += &format!("total,{},{}\n", fuel, total_fuel);
+        out
+    }
+
+    fn json(&self) -> String {
+        let modules: Vec<String> = self
+            .modules
+            .iter()
+            .map(|m| {
+                format!(
+                    "    {{\"mass\": {}, \"fuel\": {}, \"total_fuel\": {}}}",
+                    m.mass, m.fuel, m.total_fuel
+                )
+            })
+            .collect();
+        // an overflowing total is a string, as JSON numbers can't say so
+        let json = |total: Option<u64>| total.map_or("\"overflow\"".to_string(), |t| t.to_string());
+        format!(
+            "{{\n  \"modules\": [\n{}\n  ],\n  \"fuel\": {},\n  \"total_fuel\": {}\n}}\n",
+            modules.join(",\n"),
+            json(self.fuel()),
+            json(self.total_fuel())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_and_filter() {
+        let mut report = Report::new(&[12, 100756, 14, 1969]);
+        report.sort_by(Column::TotalFuel).at_least(Column::Fuel, 3);
+        let masses: Vec<u64> = report.modules.iter().map(|m| m.mass).collect();
+        assert_eq!(masses, vec![100756, 1969]);
+        assert_eq!(report.total_fuel(), Some(50346 + 966));
+    }
+
+    #[test]
+    fn test_light_and_heavy_modules() {
+        // too light to need fuel, rather than needing less than none
+        let light = Report::new(&[1, 5, 12]);
+        assert_eq!(light.modules[0].fuel, 0);
+        assert_eq!((light.fuel(), light.total_fuel()), (Some(2), Some(2)));
+        assert_eq!(light.format(Format::Csv).lines().last(), Some("total,2,2"));
+        let heavy = Report::new(&[u64::MAX; 4]);
+        assert_eq!(heavy.fuel(), None);
+        assert_eq!(
+            heavy.format(Format::Table).lines().last(),
+            Some("     total   overflow     overflow")
+        );
+        assert!(heavy
+            .format(Format::Json)
+            .ends_with("\"fuel\": \"overflow\",\n  \"total_fuel\": \"overflow\"\n}\n"));
+    }
+
+    #[test]
+    fn test_formats() {
+        let report = Report::new(&[12, 1969]);
+        assert_eq!(
+            report.format(Format::Csv),
+            "mass,fuel,total_fuel\n12,2,2\n1969,654,966\ntotal,656,968\n"
+        );
+        assert_eq!(
+            report.format(Format::Table).lines().last(),
+            Some("     total        656          968")
+        );
+        assert_eq!(
+            report.format(Format::Json),
+            "{
+  \"modules\": [
+    {\"mass\": 12, \"fuel\": 2, \"total_fuel\": 2},
+    {\"mass\": 1969, \"fuel\": 654, \"total_fuel\": 966}
+  ],
+  \"fuel\": 656,
+  \"total_fuel\": 968
+}
+"
+        );
+    }
+}
@@ -0,0 +1,364 @@
+// Points and vectors on the integer plane, for the puzzles that walk about a
+// grid. A Point is a place and a Vector the move between two of them, so
+// points can't be added together but a vector can be added to a point.
+// y grows downwards, as on every map the puzzles draw in text, where row 0 is
+// the first line: `Vector::UP` is one row back up the page, and turning left
+// is anticlockwise as the map is drawn.
+
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+pub mod grid;
+
+pub use grid::{Grid, GridError};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Vector {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub fn new(x: i32, y: i32) -> Point {
+        Point { x, y }
+    }
+
+    // from the origin
+    pub fn manhattan(&self) -> i32 {
+        (*self - Point::ORIGIN).manhattan()
+    }
+
+    pub fn manhattan_to(&self, other: Point) -> i32 {
+        (other - *self).manhattan()
+    }
+
+    pub fn chebyshev(&self) -> i32 {
+        (*self - Point::ORIGIN).chebyshev()
+    }
+
+    pub fn chebyshev_to(&self, other: Point) -> i32 {
+        (other - *self).chebyshev()
+    }
+
+    // above, right of, below and left of this point, in that order
+    pub fn neighbours4(self) -> impl Iterator<Item = Point> {
+        Vector::ORTHOGONAL.iter().map(move |&v| self + v)
+    }
+
+    // the four above, and the four diagonally next to this point as well,
+    // clockwise from above
+    pub fn neighbours8(self) -> impl Iterator<Item = Point> {
+        Vector::SURROUNDING.iter().map(move |&v| self + v)
+    }
+
+    // a quarter turn anticlockwise about `centre`
+    pub fn rotate_left_about(self, centre: Point) -> Point {
+        centre + (self - centre).turn_left()
+    }
+
+    pub fn rotate_right_about(self, centre: Point) -> Point {
+        centre + (self - centre).turn_right()
+    }
+}
+
+impl Vector {
+    pub const ZERO: Vector = Vector { x: 0, y: 0 };
+    pub const UP: Vector = Vector { x: 0, y: -1 };
+    pub const DOWN: Vector = Vector { x: 0, y: 1 };
+    pub const LEFT: Vector = Vector { x: -1, y: 0 };
+    pub const RIGHT: Vector = Vector { x: 1, y: 0 };
+
+    // clockwise from up
+    pub const ORTHOGONAL: [Vector; 4] = [Vector::UP, Vector::RIGHT, Vector::DOWN, Vector::LEFT];
+    pub const SURROUNDING: [Vector; 8] = [
+        Vector::UP,
+        Vector { x: 1, y: -1 },
+        Vector::RIGHT,
+        Vector { x: 1, y: 1 },
+        Vector::DOWN,
+        Vector { x: -1, y: 1 },
+        Vector::LEFT,
+        Vector { x: -1, y: -1 },
+    ];
+
+    pub fn new(x: i32, y: i32) -> Vector {
+        Vector { x, y }
+    }
+
+    pub fn manhattan(&self) -> i32 {
+        self.x.abs() + self.y.abs()
+    }
+
+    // the number of king's moves, where a diagonal counts as one
+    pub fn chebyshev(&self) -> i32 {
+        self.x.abs().max(self.y.abs())
+    }
+
+    // each part as -1, 0 or 1, so a straight or diagonal move becomes a
+    // single step in the same direction
+    pub fn signum(&self) -> Vector {
+        Vector::new(self.x.signum(), self.y.signum())
+    }
+
+    pub fn turn_left(&self) -> Vector {
+        Vector::new(self.y, -self.x)
+    }
+
+    pub fn turn_right(&self) -> Vector {
+        Vector::new(-self.y, self.x)
+    }
+
+    // quarter turns anticlockwise; negative turns go clockwise
+    pub fn rotate(&self, quarter_turns: i32) -> Vector {
+        match quarter_turns.rem_euclid(4) {
+            0 => *self,
+            1 => self.turn_left(),
+            2 => -*self,
+            _ => self.turn_right(),
+        }
+    }
+
+    pub fn dot(&self, other: Vector) -> i32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    // zero when the two are parallel
+    pub fn cross(&self, other: Vector) -> i32 {
+        self.x * other.y - self.y * other.x
+    }
+}
+
+impl Add<Vector> for Point {
+    type Output = Point;
+
+    fn add(self, v: Vector) -> Point {
+        Point::new(self.x + v.x, self.y + v.y)
+    }
+}
+
+impl AddAssign<Vector> for Point {
+    fn add_assign(&mut self, v: Vector) {
+        *self = *self + v;
+    }
+}
+
+impl Sub<Vector> for Point {
+    type Output = Point;
+
+    fn sub(self, v: Vector) -> Point {
+        self + -v
+    }
+}
+
+impl SubAssign<Vector> for Point {
+    fn sub_assign(&mut self, v: Vector) {
+        *self = *self - v;
+    }
+}
+
+// the vector from `other` to `self`
+impl Sub for Point {
+    type Output = Vector;
+
+    fn sub(self, other: Point) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Vector {
+        Vector::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, other: Vector) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, other: Vector) -> Vector {
+        self + -other
+    }
+}
+
+impl SubAssign for Vector {
+    fn sub_assign(&mut self, other: Vector) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<i32> for Vector {
+    type Output = Vector;
+
+    fn mul(self, n: i32) -> Vector {
+        Vector::new(self.x * n, self.y * n)
+    }
+}
+
+// the smallest box holding a set of points, corners included
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Bounds {
+    pub low: Point,
+    pub high: Point,
+}
+
+impl Bounds {
+    pub fn new(p: Point) -> Bounds {
+        Bounds { low: p, high: p }
+    }
+
+    // `None` if there are no points
+    pub fn of<I: IntoIterator<Item = Point>>(points: I) -> Option<Bounds> {
+        let mut points = points.into_iter();
+        let mut bounds = Bounds::new(points.next()?);
+        for p in points {
+            bounds.include(p);
+        }
+        Some(bounds)
+    }
+
+    // grows the box, if it has to, to take in `p`
+    pub fn include(&mut self, p: Point) {
+        self.low = Point::new(self.low.x.min(p.x), self.low.y.min(p.y));
+        self.high = Point::new(self.high.x.max(p.x), self.high.y.max(p.y));
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (self.low.x..=self.high.x).contains(&p.x) && (self.low.y..=self.high.y).contains(&p.y)
+    }
+
+    pub fn width(&self) -> i32 {
+        self.high.x - self.low.x + 1
+    }
+
+    pub fn height(&self) -> i32 {
+        self.high.y - self.low.y + 1
+    }
+
+    // the same box with `margin` more all round
+    pub fn grow(&self, margin: i32) -> Bounds {
+        let all_round = Vector::new(margin, margin);
+        Bounds {
+            low: self.low - all_round,
+            high: self.high + all_round,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let p = Point::new(3, -4);
+        let v = Vector::new(-1, 2);
+        assert_eq!(p + v, Point::new(2, -2));
+        assert_eq!(p - v, Point::new(4, -6));
+        assert_eq!(Point::new(2, -2) - p, v);
+        assert_eq!(v * 3 + v, v * 4);
+        assert_eq!(-v - v, v * -2);
+        let mut q = p;
+        q += v;
+        q -= v * 2;
+        assert_eq!(q, p - v);
+        assert_eq!(Vector::new(8, -3).signum(), Vector::new(1, -1));
+    }
+
+    #[test]
+    fn test_distances() {
+        let p = Point::new(3, -4);
+        assert_eq!(p.manhattan(), 7);
+        assert_eq!(p.chebyshev(), 4);
+        assert_eq!(p.manhattan_to(Point::new(-1, -1)), 7);
+        assert_eq!(p.chebyshev_to(Point::new(-1, -1)), 4);
+        assert_eq!(Point::ORIGIN.manhattan_to(Point::ORIGIN), 0);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let p = Point::new(5, 5);
+        let four: Vec<Point> = p.neighbours4().collect();
+        assert_eq!(
+            four,
+            vec![
+                Point::new(5, 4),
+                Point::new(6, 5),
+                Point::new(5, 6),
+                Point::new(4, 5)
+            ]
+        );
+        let eight: Vec<Point> = p.neighbours8().collect();
+        assert_eq!(
+            eight,
+            vec![
+                Point::new(5, 4),
+                Point::new(6, 4),
+                Point::new(6, 5),
+                Point::new(6, 6),
+                Point::new(5, 6),
+                Point::new(4, 6),
+                Point::new(4, 5),
+                Point::new(4, 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rotations() {
+        assert_eq!(Vector::UP.turn_left(), Vector::LEFT);
+        assert_eq!(Vector::UP.turn_right(), Vector::RIGHT);
+        let v = Vector::new(2, 1);
+        for turns in -5i32..5 {
+            let mut turned = v;
+            for _ in 0..turns.rem_euclid(4) {
+                turned = turned.turn_left();
+            }
+            assert_eq!(v.rotate(turns), turned);
+            assert_eq!(v.rotate(turns).manhattan(), v.manhattan());
+        }
+        let centre = Point::new(1, 1);
+        assert_eq!(
+            Point::new(3, 1).rotate_left_about(centre),
+            Point::new(1, -1)
+        );
+        assert_eq!(
+            Point::new(3, 1).rotate_right_about(centre),
+            Point::new(1, 3)
+        );
+    }
+
+    #[test]
+    fn test_bounds() {
+        let points = [Point::new(2, -1), Point::new(-3, 4), Point::new(0, 0)];
+        let bounds = Bounds::of(points.iter().cloned()).unwrap();
+        assert_eq!(bounds.low, Point::new(-3, -1));
+        assert_eq!(bounds.high, Point::new(2, 4));
+        assert_eq!((bounds.width(), bounds.height()), (6, 6));
+        assert!(points.iter().all(|&p| bounds.contains(p)));
+        assert!(!bounds.contains(Point::new(3, 0)));
+        assert!(bounds.grow(1).contains(Point::new(3, 0)));
+        assert_eq!(Bounds::of(Vec::new()), None);
+    }
+}
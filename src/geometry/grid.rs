@@ -0,0 +1,296 @@
+// A rectangle of cells indexed by Point, as read from a map drawn in text:
+// row 0 is the first line, so `Vector::DOWN` is the next line down the page.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::{Index, IndexMut};
+use std::path::Path;
+use std::str::FromStr;
+
+use super::{Bounds, Point};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    // row by row
+    cells: Vec<T>,
+}
+
+#[derive(Debug)]
+pub enum GridError {
+    Io(io::Error),
+    // a line, numbered from 1, that's a different length to the first
+    Ragged {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    // a character the cells can't be made from, at a line and column
+    // numbered from 1
+    Cell {
+        line: usize,
+        column: usize,
+        c: char,
+    },
+}
+
+impl From<io::Error> for GridError {
+    fn from(e: io::Error) -> Self {
+        GridError::Io(e)
+    }
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::Io(e) => write!(f, "{}", e),
+            GridError::Ragged {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: expected {} characters, got {}",
+                line, expected, found
+            ),
+            GridError::Cell { line, column, c } => {
+                write!(f, "line {}, column {}: unexpected {:?}", line, column, c)
+            }
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    // one line per row, every line the same length; blank lines at the end
+    // are left off
+    pub fn parse_with<F: Fn(char) -> Option<T>>(s: &str, cell: F) -> Result<Grid<T>, GridError> {
+        let lines: Vec<&str> = s.trim_end_matches(['\n', '\r']).lines().collect();
+        let width = lines.first().map_or(0, |l| l.chars().count());
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for (i, line) in lines.iter().enumerate() {
+            let found = line.chars().count();
+            if found != width {
+                return Err(GridError::Ragged {
+                    line: i + 1,
+                    expected: width,
+                    found,
+                });
+            }
+            for (j, c) in line.chars().enumerate() {
+                cells.push(cell(c).ok_or(GridError::Cell {
+                    line: i + 1,
+                    column: j + 1,
+                    c,
+                })?);
+            }
+        }
+        Ok(Grid {
+            width,
+            height: lines.len(),
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // `None` for an empty grid
+    pub fn bounds(&self) -> Option<Bounds> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        Some(Bounds {
+            low: Point::ORIGIN,
+            high: Point::new(self.width as i32 - 1, self.height as i32 - 1),
+        })
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.offset(p).is_some()
+    }
+
+    fn offset(&self, p: Point) -> Option<usize> {
+        if p.x < 0 || p.y < 0 || p.x as usize >= self.width || p.y as usize >= self.height {
+            return None;
+        }
+        Some(p.y as usize * self.width + p.x as usize)
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.offset(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.offset(p).map(move |i| &mut self.cells[i])
+    }
+
+    // every point in the grid, a row at a time
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| Point::new((i % width) as i32, (i / width) as i32))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    // the neighbours of `p` that are inside the grid
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        p.neighbours4().filter(move |&q| self.contains(q))
+    }
+
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        p.neighbours8().filter(move |&q| self.contains(q))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl Grid<char> {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Grid<char>, GridError> {
+        Grid::from_str(&fs::read_to_string(path)?)
+    }
+}
+
+impl FromStr for Grid<char> {
+    type Err = GridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grid::parse_with(s, Some)
+    }
+}
+
+// panics outside the grid
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        self.get(p)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", p))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", p))
+    }
+}
+
+// drawn back out as text, a line per row
+impl fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            writeln!(f, "{}", row.iter().collect::<String>())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Vector;
+    use super::*;
+
+    const MAP: &str = ".#..
+..#.
+#...
+";
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::from_str(MAP).unwrap();
+        assert_eq!((grid.width(), grid.height()), (4, 3));
+        assert_eq!(grid[Point::new(1, 0)], '#');
+        assert_eq!(grid.get(Point::new(0, 2)), Some(&'#'));
+        assert_eq!(grid.get(Point::new(4, 0)), None);
+        assert_eq!(grid.get(Point::new(0, -1)), None);
+        assert_eq!(grid.to_string(), MAP);
+        let walls: Vec<Point> = grid
+            .iter()
+            .filter(|&(_, &c)| c == '#')
+            .map(|(p, _)| p)
+            .collect();
+        assert_eq!(
+            walls,
+            vec![Point::new(1, 0), Point::new(2, 1), Point::new(0, 2)]
+        );
+        assert_eq!(grid.bounds().unwrap().high, Point::new(3, 2));
+    }
+
+    #[test]
+    fn test_directions_match_the_page() {
+        let grid = Grid::from_str("abc\ndef\nghi\n").unwrap();
+        let centre = Point::new(1, 1);
+        assert_eq!(grid[centre], 'e');
+        assert_eq!(grid[centre + Vector::UP], 'b');
+        assert_eq!(grid[centre + Vector::DOWN], 'h');
+        assert_eq!(grid[centre + Vector::UP.turn_left()], 'd');
+        assert_eq!(grid[centre + Vector::UP.turn_right()], 'f');
+        let around: String = grid.neighbours8(centre).map(|p| grid[p]).collect();
+        assert_eq!(around, "bcfihgda");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        match Grid::from_str(".#.\n..\n") {
+            Err(GridError::Ragged {
+                line: 2,
+                expected: 3,
+                found: 2,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+        let walls = Grid::parse_with("..\n.x", |c| match c {
+            '.' => Some(false),
+            '#' => Some(true),
+            _ => None,
+        });
+        assert_eq!(
+            walls.unwrap_err().to_string(),
+            "line 2, column 2: unexpected 'x'"
+        );
+        let empty = Grid::from_str("").unwrap();
+        assert_eq!(
+            (empty.width(), empty.height(), empty.bounds()),
+            (0, 0, None)
+        );
+    }
+
+    #[test]
+    fn test_neighbours_and_updates() {
+        let mut grid = Grid::new(3, 2, 0);
+        assert_eq!(grid.neighbours4(Point::ORIGIN).count(), 2);
+        assert_eq!(grid.neighbours8(Point::ORIGIN).count(), 3);
+        assert_eq!(grid.neighbours8(Point::new(1, 1)).count(), 5);
+        for p in grid.points().collect::<Vec<_>>() {
+            grid[p] = p.x + 10 * p.y;
+        }
+        *grid.get_mut(Point::new(2, 1)).unwrap() += 100;
+        assert_eq!(grid.map(|&n| n % 10)[Point::new(2, 1)], 2);
+        assert_eq!(grid[Point::new(2, 1)], 112);
+    }
+}
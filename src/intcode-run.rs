@@ -0,0 +1,276 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::io::BufRead;
+use std::process;
+use std::str::FromStr;
+
+use aoc_2019::intcode;
+use aoc_2019::intcode::patch::Patch;
+use aoc_2019::intcode::session::{self, Recorder, Session};
+use aoc_2019::intcode::{Machine, MachineState};
+
+const USAGE: &str = "usage: intcode-run PROGRAM [options]
+
+  --input VALUES        comma separated input values, may be repeated
+  --input-file FILE     read input values from FILE instead of stdin
+  --format FORMAT       print outputs as `lines` (the default), `csv` or `ascii`
+  --set ADDRESS=VALUE   poke a value into the program before it runs
+  --patch FILE          apply a file of ADDRESS=VALUE lines
+  --step-limit N        give up after N instructions
+  --trace               print every instruction to stderr as it runs
+  --dump-memory         print the final memory once the program stops
+  --record FILE         save every input and output, with its step, to FILE
+  --replay FILE         rerun a recorded session and check the outputs match
+
+Without --input or --input-file, values are read from stdin as the program
+asks for them. --replay only takes --set and --patch alongside it.";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Lines,
+    Csv,
+    Ascii,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lines" => Ok(Format::Lines),
+            "csv" => Ok(Format::Csv),
+            "ascii" => Ok(Format::Ascii),
+            _ => Err(format!("unknown format {}", s)),
+        }
+    }
+}
+
+struct Options {
+    program: String,
+    patch: Patch,
+    // `None` means read from stdin
+    inputs: Option<Vec<i32>>,
+    format: Format,
+    step_limit: Option<usize>,
+    trace: bool,
+    dump_memory: bool,
+    record: Option<String>,
+    replay: Option<String>,
+}
+
+fn parse_values(s: &str) -> Result<Vec<i32>, String> {
+    s.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|v| !v.is_empty())
+        .map(|v| i32::from_str(v).map_err(|_| format!("{:?} is not a number", v)))
+        .collect()
+}
+
+fn parse_args(mut args: env::Args) -> Result<Options, String> {
+    args.next();
+    let mut options = Options {
+        program: String::new(),
+        patch: Patch::new(),
+        inputs: None,
+        format: Format::Lines,
+        step_limit: None,
+        trace: false,
+        dump_memory: false,
+        record: None,
+        replay: None,
+    };
+    let mut program = None;
+    // the first option given that only makes sense for a fresh run
+    let mut run_only = None;
+    while let Some(arg) = args.next() {
+        if let "--format" | "--step-limit" | "--trace" | "--dump-memory" = arg.as_str() {
+            run_only.get_or_insert_with(|| arg.clone());
+        }
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+        match arg.as_str() {
+            "--input" => {
+                let values = parse_values(&value("--input")?)?;
+                options.inputs.get_or_insert_with(Vec::new).extend(values);
+            }
+            "--input-file" => {
+                let path = value("--input-file")?;
+                let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+                let values = parse_values(&text).map_err(|e| format!("{}: {}", path, e))?;
+                options.inputs.get_or_insert_with(Vec::new).extend(values);
+            }
+            "--format" => options.format = Format::from_str(&value("--format")?)?,
+            "--set" => {
+                let entry = value("--set")?;
+                options
+                    .patch
+                    .set_from_str(&entry)
+                    .map_err(|_| format!("--set needs ADDRESS=VALUE, not {:?}", entry))?;
+            }
+            "--patch" => {
+                let path = value("--patch")?;
+                let patch = Patch::load(&path).map_err(|e| format!("{}: {}", path, e))?;
+                for (address, value) in patch.iter() {
+                    options.patch.set(address, value);
+                }
+            }
+            "--step-limit" => {
+                let limit = value("--step-limit")?;
+                let limit = usize::from_str(&limit)
+                    .map_err(|_| format!("--step-limit needs a number, not {:?}", limit))?;
+                options.step_limit = Some(limit);
+            }
+            "--trace" => options.trace = true,
+            "--dump-memory" => options.dump_memory = true,
+            "--record" => options.record = Some(value("--record")?),
+            "--replay" => options.replay = Some(value("--replay")?),
+            _ if arg.starts_with("--") || program.is_some() => {
+                return Err(format!("unexpected argument {}", arg))
+            }
+            _ => program = Some(arg),
+        }
+    }
+    options.program = program.ok_or("no program given")?;
+    if options.replay.is_some() && (options.inputs.is_some() || options.record.is_some()) {
+        return Err("--replay takes its inputs from the session".to_string());
+    }
+    if let (Some(_), Some(flag)) = (&options.replay, run_only) {
+        return Err(format!("--replay can't be combined with {}", flag));
+    }
+    Ok(options)
+}
+
+// writes outputs as they arrive, so that a long-running program shows progress
+struct Printer {
+    format: Format,
+    count: usize,
+}
+
+impl Printer {
+    fn print(&mut self, value: i32) {
+        match self.format {
+            Format::Lines => println!("{}", value),
+            Format::Csv if self.count == 0 => print!("{}", value),
+            Format::Csv => print!(",{}", value),
+            Format::Ascii => match value {
+                0..=127 => print!("{}", value as u8 as char),
+                // anything else is usually an answer tacked onto the end
+                _ => println!("{}", value),
+            },
+        }
+        self.count += 1;
+    }
+
+    fn finish(&self) {
+        if self.format == Format::Csv && self.count > 0 {
+            println!();
+        }
+    }
+}
+
+fn trace<M: Machine>(machine: &M) {
+    let ip = machine.instruction_pointer();
+    let memory = machine.memory();
+    let cells = match memory.get(ip) {
+        Some(&instruction) => {
+            let end = (ip + 1 + intcode::parameter_count(instruction)).min(memory.len());
+            memory[ip..end]
+                .iter()
+                .map(i32::to_string)
+                .collect::<Vec<String>>()
+                .join(",")
+        }
+        None => "-".to_string(),
+    };
+    eprintln!("{:>8} {:>6}: {}", machine.steps(), ip, cells);
+}
+
+fn replay(program: intcode::ProgramData, path: &str) -> Result<(), String> {
+    let recorded = Session::load(path).map_err(|e| format!("{}: {}", path, e))?;
+    let replayed = session::replay::<intcode::Interpreter>(program, &recorded)
+        .map_err(|divergence| divergence.to_string())?;
+    println!("replayed {} events, all matching", replayed.events.len());
+    Ok(())
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let mut program =
+        intcode::load(&options.program).map_err(|e| format!("{}: {}", options.program, e))?;
+    options
+        .patch
+        .apply(&mut program)
+        .map_err(|e| e.to_string())?;
+    if let Some(path) = &options.replay {
+        return replay(program, path);
+    }
+
+    let mut machine: Recorder<intcode::Interpreter> = Recorder::new(program);
+    machine.set_step_limit(options.step_limit);
+    let reading_stdin = options.inputs.is_none();
+    for &value in options.inputs.iter().flatten() {
+        machine.input(value);
+    }
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut printer = Printer {
+        format: options.format,
+        count: 0,
+    };
+    let mut traced = None;
+    let result = loop {
+        // an instruction waiting on input is only traced once
+        if options.trace && traced != Some(machine.steps()) {
+            trace(&machine);
+            traced = Some(machine.steps());
+        }
+        match machine.step() {
+            None => {}
+            Some(MachineState::Input) if reading_stdin => match lines.next() {
+                Some(Ok(line)) => match parse_values(&line) {
+                    Ok(values) => {
+                        for value in values {
+                            machine.input(value);
+                        }
+                    }
+                    Err(e) => break Err(e),
+                },
+                Some(Err(e)) => break Err(e.to_string()),
+                None => break Err("stdin ran out before the program finished".to_string()),
+            },
+            Some(MachineState::Input) => break Err("the program wants more input".to_string()),
+            Some(MachineState::Output) => {
+                while let Some(value) = machine.output() {
+                    printer.print(value);
+                }
+            }
+            Some(MachineState::Finished) => break Ok(()),
+            Some(MachineState::Error(fault)) => {
+                break Err(format!("{:?} after {} steps", fault, machine.steps()))
+            }
+        }
+    };
+    printer.finish();
+    if let Some(path) = &options.record {
+        machine
+            .session()
+            .save(path)
+            .map_err(|e| format!("{}: {}", path, e))?;
+    }
+    if options.dump_memory {
+        let memory: Vec<String> = machine.memory().iter().map(i32::to_string).collect();
+        println!("{}", memory.join(","));
+    }
+    result
+}
+
+fn main() {
+    let options = parse_args(env::args()).unwrap_or_else(|e| {
+        eprintln!("intcode-run: {}", e);
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    });
+    if let Err(e) = run(options) {
+        eprintln!("intcode-run: {}", e);
+        process::exit(1);
+    }
+}
@@ -1,27 +1,399 @@
+use std::collections::VecDeque;
+use std::convert::TryInto;
 use std::fs;
 use std::io;
-use std::io::BufRead;
 use std::path::Path;
 use std::str::FromStr;
 
+pub mod amplifier;
+#[cfg(test)]
+mod conformance;
+pub mod fuzz;
+pub mod patch;
+pub mod session;
+
 pub type ProgramData = Vec<i32>;
 
+// a program as the puzzles give it, comma separated; a cell that isn't a
+// number is an InvalidData error naming its address
+pub fn parse(s: &str) -> io::Result<ProgramData> {
+    s.split(',')
+        .map(str::trim)
+        .enumerate()
+        .map(|(address, cell)| {
+            i32::from_str(cell).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("cell {}: expected a number, not {:?}", address, cell),
+                )
+            })
+        })
+        .collect()
+}
+
 pub fn load<P: AsRef<Path>>(path: P) -> io::Result<ProgramData> {
-    let f = fs::File::open(path)?;
-    let reader = io::BufReader::new(f);
-    let program: Vec<i32> = reader
-        .split(b',')
-        .map(Result::unwrap)
-        .map(|v| String::from_utf8(v))
-        .map(Result::unwrap)
-        .map(|s| i32::from_str((&s).trim()))
-        .map(Result::unwrap)
-        .collect();
-    Ok(program)
+    parse(&fs::read_to_string(path)?)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Fault {
+    // the instruction pointer hit something that isn't an opcode
+    InvalidOpcode(i32),
+    // a read, write or jump outside of memory
+    InvalidAddress(i32),
+    Overflow,
+    // the machine ran for longer than it was allowed to
+    StepLimit,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MachineState {
+    // an output value is waiting to be taken
+    Output,
+    // the program wants input and none has been provided
+    Input,
+    Finished,
+    Error(Fault),
+}
+
+// how many parameters follow an instruction, ignoring its parameter modes
+pub fn parameter_count(instruction: i32) -> usize {
+    match instruction % 100 {
+        1 | 2 | 7 | 8 => 3,
+        5 | 6 => 2,
+        3 | 4 => 1,
+        _ => 0,
+    }
 }
 
 pub trait Machine {
-    fn run(&mut self, program: ProgramData) -> ProgramData;
+    // build a machine around a program image; the image is kept so that
+    // `reset` can put it back
+    fn new(image: ProgramData) -> Self
+    where
+        Self: Sized;
+
+    // execute a single instruction, returning a state if the machine stopped
+    fn step(&mut self) -> Option<MachineState>;
+
+    // run until the program halts, produces an output or needs more input
+    fn run(&mut self) -> MachineState {
+        loop {
+            if let Some(state) = self.step() {
+                return state;
+            }
+        }
+    }
 
+    // restore the original program image and clear every register and queue,
+    // without giving up the memory that has already been allocated
     fn reset(&mut self);
+
+    fn input(&mut self, value: i32);
+
+    fn output(&mut self) -> Option<i32>;
+
+    fn memory(&self) -> &[i32];
+
+    fn instruction_pointer(&self) -> usize;
+
+    // the number of instructions executed since the last reset
+    fn steps(&self) -> usize;
+
+    // stop with `Fault::StepLimit` rather than execute more than `limit`
+    // instructions; the limit survives a reset
+    fn set_step_limit(&mut self, limit: Option<usize>);
+
+    // run with a fixed set of inputs from a clean state, collecting every output
+    fn run_with_inputs(&mut self, inputs: &[i32]) -> (MachineState, Vec<i32>) {
+        self.reset();
+        for &value in inputs {
+            self.input(value);
+        }
+        let mut outputs = Vec::new();
+        loop {
+            match self.run() {
+                MachineState::Output => {
+                    while let Some(value) = self.output() {
+                        outputs.push(value);
+                    }
+                }
+                state => return (state, outputs),
+            }
+        }
+    }
+}
+
+pub struct Interpreter {
+    image: ProgramData,
+    memory: ProgramData,
+    instruction_pointer: usize,
+    steps: usize,
+    step_limit: Option<usize>,
+    input_deque: VecDeque<i32>,
+    output_deque: VecDeque<i32>,
+}
+
+enum ParameterMode {
+    Position,
+    Immediate,
+}
+
+impl Interpreter {
+    // for poking values in between runs; `reset` puts the image back
+    pub fn memory_mut(&mut self) -> &mut [i32] {
+        &mut self.memory
+    }
+
+    fn get_parameter_mode(opcode: i32, parameter_number: u32) -> ParameterMode {
+        match (opcode / 10i32.pow(parameter_number + 1)) % 10 {
+            1 => ParameterMode::Immediate,
+            _ => ParameterMode::Position,
+        }
+    }
+
+    fn address(&self, address: i32) -> Result<usize, Fault> {
+        match address.try_into() {
+            Ok(a) if a < self.memory.len() => Ok(a),
+            _ => Err(Fault::InvalidAddress(address)),
+        }
+    }
+
+    fn read(&self, address: i32) -> Result<i32, Fault> {
+        Ok(self.memory[self.address(address)?])
+    }
+
+    fn store(&mut self, location: i32, value: i32) -> Result<(), Fault> {
+        let a = self.address(location)?;
+        self.memory[a] = value;
+        Ok(())
+    }
+
+    fn instruction(&self) -> Result<i32, Fault> {
+        match self.memory.get(self.instruction_pointer) {
+            Some(&instruction) => Ok(instruction),
+            None => Err(Fault::InvalidAddress(self.instruction_pointer as i32)),
+        }
+    }
+
+    fn parameter(&self, parameter: u32) -> Result<i32, Fault> {
+        self.read((self.instruction_pointer + parameter as usize) as i32)
+    }
+
+    fn load_parameter(&self, parameter: u32) -> Result<i32, Fault> {
+        let value = self.parameter(parameter)?;
+        match Self::get_parameter_mode(self.instruction()?, parameter) {
+            ParameterMode::Immediate => Ok(value),
+            ParameterMode::Position => self.read(value),
+        }
+    }
+
+    fn arithmetic<F: Fn(i32, i32) -> Option<i32>>(&mut self, f: F) -> Result<(), Fault> {
+        let a = self.load_parameter(1)?;
+        let b = self.load_parameter(2)?;
+        let value = f(a, b).ok_or(Fault::Overflow)?;
+        self.store(self.parameter(3)?, value)?;
+        self.instruction_pointer += 4;
+        Ok(())
+    }
+
+    fn jump<F: Fn(i32) -> bool>(&mut self, condition: F) -> Result<(), Fault> {
+        let a = self.load_parameter(1)?;
+        let b = self.load_parameter(2)?;
+        if condition(a) {
+            self.instruction_pointer = self.address(b)?;
+        } else {
+            self.instruction_pointer += 3;
+        }
+        Ok(())
+    }
+
+    fn read_input(&mut self) -> Result<bool, Fault> {
+        let destination = self.parameter(1)?;
+        self.address(destination)?;
+        match self.input_deque.pop_front() {
+            Some(value) => {
+                self.store(destination, value)?;
+                self.instruction_pointer += 2;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn write_output(&mut self) -> Result<(), Fault> {
+        let a = self.load_parameter(1)?;
+        self.output_deque.push_back(a);
+        self.instruction_pointer += 2;
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<Option<MachineState>, Fault> {
+        if self.step_limit.is_some_and(|limit| self.steps >= limit) {
+            return Err(Fault::StepLimit);
+        }
+        let instruction = self.instruction()?;
+        let state = match instruction % 100 {
+            1 => self.arithmetic(i32::checked_add).map(|_| None),
+            2 => self.arithmetic(i32::checked_mul).map(|_| None),
+            3 => self.read_input().map(|read| {
+                if read {
+                    None
+                } else {
+                    Some(MachineState::Input)
+                }
+            }),
+            4 => self.write_output().map(|_| Some(MachineState::Output)),
+            5 => self.jump(|a| a != 0).map(|_| None),
+            6 => self.jump(|a| a == 0).map(|_| None),
+            7 => self
+                .arithmetic(|a, b| Some(if a < b { 1 } else { 0 }))
+                .map(|_| None),
+            8 => self
+                .arithmetic(|a, b| Some(if a == b { 1 } else { 0 }))
+                .map(|_| None),
+            99 => return Ok(Some(MachineState::Finished)),
+            _ => Err(Fault::InvalidOpcode(instruction)),
+        }?;
+        if state != Some(MachineState::Input) {
+            self.steps += 1;
+        }
+        Ok(state)
+    }
+}
+
+impl Machine for Interpreter {
+    fn new(image: ProgramData) -> Interpreter {
+        Interpreter {
+            memory: image.clone(),
+            image,
+            instruction_pointer: 0,
+            steps: 0,
+            step_limit: None,
+            input_deque: VecDeque::new(),
+            output_deque: VecDeque::new(),
+        }
+    }
+
+    fn step(&mut self) -> Option<MachineState> {
+        match self.execute() {
+            Ok(state) => state,
+            Err(fault) => Some(MachineState::Error(fault)),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.memory.copy_from_slice(&self.image);
+        self.instruction_pointer = 0;
+        self.steps = 0;
+        self.input_deque.clear();
+        self.output_deque.clear();
+    }
+
+    fn input(&mut self, value: i32) {
+        self.input_deque.push_back(value);
+    }
+
+    fn output(&mut self) -> Option<i32> {
+        self.output_deque.pop_front()
+    }
+
+    fn memory(&self) -> &[i32] {
+        &self.memory
+    }
+
+    fn instruction_pointer(&self) -> usize {
+        self.instruction_pointer
+    }
+
+    fn steps(&self) -> usize {
+        self.steps
+    }
+
+    fn set_step_limit(&mut self, limit: Option<usize>) {
+        self.step_limit = limit;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("1,0, 0,0,99\n").unwrap(), vec![1, 0, 0, 0, 99]);
+        let error = |s: &str| parse(s).unwrap_err();
+        assert_eq!(error("1,0,0,0,99,").kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            error("1,0,0,0,99,\n").to_string(),
+            "cell 5: expected a number, not \"\""
+        );
+        assert_eq!(
+            error("1,x,0").to_string(),
+            "cell 1: expected a number, not \"x\""
+        );
+        assert_eq!(load("data/day2.txt").unwrap().len(), 161);
+    }
+
+    #[test]
+    fn test_reset_restores_memory() {
+        let mut machine = Interpreter::new(vec![1, 0, 0, 0, 99]);
+        assert_eq!(machine.run(), MachineState::Finished);
+        assert_eq!(machine.memory(), &[2, 0, 0, 0, 99]);
+        machine.reset();
+        assert_eq!(machine.memory(), &[1, 0, 0, 0, 99]);
+        assert_eq!(machine.run(), MachineState::Finished);
+        assert_eq!(machine.memory(), &[2, 0, 0, 0, 99]);
+    }
+
+    #[test]
+    fn test_run_repeatedly_with_different_inputs() {
+        let mut machine = Interpreter::new(vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
+        assert_eq!(
+            machine.run_with_inputs(&[8]),
+            (MachineState::Finished, vec![1])
+        );
+        assert_eq!(
+            machine.run_with_inputs(&[7]),
+            (MachineState::Finished, vec![0])
+        );
+        assert_eq!(
+            machine.run_with_inputs(&[8]),
+            (MachineState::Finished, vec![1])
+        );
+    }
+
+    #[test]
+    fn test_waits_for_input() {
+        let mut machine = Interpreter::new(vec![3, 0, 4, 0, 99]);
+        assert_eq!(machine.run(), MachineState::Input);
+        machine.input(42);
+        assert_eq!(machine.run(), MachineState::Output);
+        assert_eq!(machine.output(), Some(42));
+        assert_eq!(machine.run(), MachineState::Finished);
+    }
+
+    #[test]
+    fn test_faults() {
+        let mut machine = Interpreter::new(vec![1, 0, 0, 7, 99]);
+        assert_eq!(machine.run(), MachineState::Error(Fault::InvalidAddress(7)));
+        let mut machine = Interpreter::new(vec![42]);
+        assert_eq!(machine.run(), MachineState::Error(Fault::InvalidOpcode(42)));
+        let mut machine = Interpreter::new(vec![1102, 65536, 65536, 0, 99]);
+        assert_eq!(machine.run(), MachineState::Error(Fault::Overflow));
+        let mut machine = Interpreter::new(vec![1105, 1, 7]);
+        assert_eq!(machine.run(), MachineState::Error(Fault::InvalidAddress(7)));
+    }
+
+    #[test]
+    fn test_step_limit() {
+        // jumps back to itself forever
+        let mut machine = Interpreter::new(vec![1105, 1, 0]);
+        machine.set_step_limit(Some(10));
+        assert_eq!(machine.run(), MachineState::Error(Fault::StepLimit));
+        assert_eq!(machine.steps(), 10);
+        machine.reset();
+        assert_eq!(machine.steps(), 0);
+        assert_eq!(machine.run(), MachineState::Error(Fault::StepLimit));
+    }
 }
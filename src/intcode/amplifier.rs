@@ -0,0 +1,138 @@
+use super::{Machine, MachineState, ProgramData};
+
+pub fn permutations(size: usize) -> Permutations {
+    Permutations {
+        idxs: (0..size).collect(),
+        swaps: vec![0; size],
+        i: 0,
+    }
+}
+pub struct Permutations {
+    idxs: Vec<usize>,
+    swaps: Vec<usize>,
+    i: usize,
+}
+impl Iterator for Permutations {
+    type Item = Vec<usize>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.i > 0 {
+            loop {
+                if self.i >= self.swaps.len() {
+                    return None;
+                }
+                if self.swaps[self.i] < self.i {
+                    break;
+                }
+                self.swaps[self.i] = 0;
+                self.i += 1;
+            }
+            self.idxs.swap(self.i, (self.i & 1) * self.swaps[self.i]);
+            self.swaps[self.i] += 1;
+        }
+        self.i = 1;
+        Some(self.idxs.clone())
+    }
+}
+
+// a bank of amplifiers, one machine per phase setting; the machines are reset
+// rather than rebuilt between runs
+pub struct Amplifiers<M: Machine> {
+    machines: Vec<M>,
+}
+
+impl<M: Machine> Amplifiers<M> {
+    pub fn new(program: &ProgramData, count: usize) -> Amplifiers<M> {
+        Amplifiers {
+            machines: (0..count).map(|_| M::new(program.clone())).collect(),
+        }
+    }
+
+    // each amplifier runs to completion and hands its output to the next;
+    // `None` if one halts without any output, or doesn't halt cleanly
+    pub fn serial(&mut self, phases: &[i32]) -> Option<i32> {
+        let machine = self.machines.first_mut()?;
+        let mut signal = 0;
+        for &phase in phases {
+            match machine.run_with_inputs(&[phase, signal]) {
+                (MachineState::Finished, outputs) => signal = *outputs.first()?,
+                _ => return None,
+            }
+        }
+        Some(signal)
+    }
+
+    // the last amplifier feeds back into the first until they all halt;
+    // `None` if there are more phases than amplifiers, or one of them faults
+    // or waits on input that never comes
+    pub fn feedback(&mut self, phases: &[i32]) -> Option<i32> {
+        if phases.len() > self.machines.len() {
+            return None;
+        }
+        for (machine, &phase) in self.machines.iter_mut().zip(phases.iter()) {
+            machine.reset();
+            machine.input(phase);
+        }
+        let mut register: i32 = 0;
+        for i in (0..phases.len()).cycle() {
+            let m = &mut self.machines[i];
+            m.input(register);
+            match m.run() {
+                MachineState::Output => register = m.output()?,
+                MachineState::Finished => break,
+                MachineState::Input | MachineState::Error(_) => return None,
+            }
+        }
+        Some(register)
+    }
+
+    // the highest signal over every ordering of the given phase settings;
+    // `None` if any ordering fails to give a signal
+    pub fn max_signal(&mut self, phases: &[i32], feedback: bool) -> Option<i32> {
+        let mut best = None;
+        for order in permutations(phases.len()) {
+            let ordered: Vec<i32> = order.iter().map(|&i| phases[i]).collect();
+            let signal = if feedback {
+                self.feedback(&ordered)?
+            } else {
+                self.serial(&ordered)?
+            };
+            best = best.max(Some(signal));
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::Interpreter;
+
+    // reads its phase and signal, and outputs their sum
+    const ADDER: [i32; 13] = [3, 11, 3, 12, 1, 11, 12, 11, 4, 11, 99, 0, 0];
+
+    #[test]
+    fn test_serial_without_output() {
+        let mut adders: Amplifiers<Interpreter> = Amplifiers::new(&ADDER.to_vec(), 1);
+        assert_eq!(adders.serial(&[1, 2, 3]), Some(6));
+        assert_eq!(adders.max_signal(&[1, 2, 3], false), Some(6));
+        // halts straight away
+        let mut silent: Amplifiers<Interpreter> = Amplifiers::new(&vec![99], 1);
+        assert_eq!(silent.serial(&[0, 1]), None);
+        assert_eq!(silent.max_signal(&[0, 1], false), None);
+        let mut none: Amplifiers<Interpreter> = Amplifiers::new(&vec![99], 0);
+        assert_eq!(none.serial(&[0]), None);
+    }
+
+    #[test]
+    fn test_feedback_failures() {
+        let mut adders: Amplifiers<Interpreter> = Amplifiers::new(&ADDER.to_vec(), 2);
+        assert_eq!(adders.feedback(&[1, 2, 3]), None);
+        // wants two inputs for every output, forever
+        let mut hungry: Amplifiers<Interpreter> =
+            Amplifiers::new(&vec![3, 9, 3, 9, 4, 9, 1105, 1, 0, 0], 2);
+        assert_eq!(hungry.feedback(&[1, 2]), None);
+        let mut faulty: Amplifiers<Interpreter> = Amplifiers::new(&vec![3, 9, 42], 2);
+        assert_eq!(faulty.feedback(&[1, 2]), None);
+        assert_eq!(faulty.max_signal(&[1, 2], true), None);
+    }
+}
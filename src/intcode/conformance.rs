@@ -0,0 +1,170 @@
+// Runs every fixture in data/intcode against a machine implementation. A
+// fixture file is a set of blank-line separated blocks of `key: value` lines:
+// the first block holds the `program`, and each later block is either a run
+// (`input`, `output` and optionally the final `memory`) or an amplifier check
+// (`amplifiers: serial|feedback`, the best `phases` and their `max_signal`).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use super::amplifier::Amplifiers;
+use super::{Interpreter, Machine, MachineState, ProgramData};
+
+const FIXTURES: &str = "data/intcode";
+
+#[derive(Debug, Default)]
+struct Run {
+    input: Vec<i32>,
+    output: Vec<i32>,
+    memory: Option<Vec<i32>>,
+}
+
+#[derive(Debug)]
+struct AmplifierCheck {
+    feedback: bool,
+    phases: Vec<i32>,
+    max_signal: i32,
+}
+
+#[derive(Debug)]
+struct Fixture {
+    path: PathBuf,
+    program: ProgramData,
+    runs: Vec<Run>,
+    amplifiers: Vec<AmplifierCheck>,
+}
+
+fn parse_list(value: &str) -> Vec<i32> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| i32::from_str(s).unwrap())
+        .collect()
+}
+
+fn parse_fixture(path: &Path) -> Fixture {
+    let text = fs::read_to_string(path).unwrap();
+    let mut fixture = Fixture {
+        path: path.to_path_buf(),
+        program: Vec::new(),
+        runs: Vec::new(),
+        amplifiers: Vec::new(),
+    };
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.starts_with('#'))
+        .collect();
+    for block in lines.split(|l| l.is_empty()).filter(|b| !b.is_empty()) {
+        let mut run = Run::default();
+        let mut is_run = false;
+        let mut feedback = None;
+        let mut phases = Vec::new();
+        let mut max_signal = None;
+        for line in block {
+            let sep = line
+                .find(':')
+                .unwrap_or_else(|| panic!("{}: no key in {:?}", path.display(), line));
+            let value = line[(sep + 1)..].trim();
+            match &line[0..sep] {
+                "program" => fixture.program = parse_list(value),
+                "input" => {
+                    is_run = true;
+                    run.input = parse_list(value);
+                }
+                "output" => run.output = parse_list(value),
+                "memory" => run.memory = Some(parse_list(value)),
+                "amplifiers" => feedback = Some(value == "feedback"),
+                "phases" => phases = parse_list(value),
+                "max_signal" => max_signal = Some(i32::from_str(value).unwrap()),
+                key => panic!("{}: unknown key {:?}", path.display(), key),
+            }
+        }
+        if is_run {
+            fixture.runs.push(run);
+        }
+        if let Some(feedback) = feedback {
+            fixture.amplifiers.push(AmplifierCheck {
+                feedback,
+                phases,
+                max_signal: max_signal.unwrap(),
+            });
+        }
+    }
+    assert!(
+        !fixture.program.is_empty(),
+        "{}: no program",
+        path.display()
+    );
+    fixture
+}
+
+fn fixtures() -> Vec<Fixture> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(FIXTURES)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == "txt"))
+        .collect();
+    paths.sort();
+    paths.iter().map(|p| parse_fixture(p)).collect()
+}
+
+fn check_run<M: Machine>(machine: &mut M, fixture: &Fixture, run: &Run) {
+    let name = fixture.path.display();
+    let (state, output) = machine.run_with_inputs(&run.input);
+    assert_eq!(state, MachineState::Finished, "{} {:?}", name, run.input);
+    assert_eq!(output, run.output, "{} {:?}", name, run.input);
+    if let Some(memory) = &run.memory {
+        assert_eq!(machine.memory(), &memory[..], "{} {:?}", name, run.input);
+    }
+}
+
+fn check_amplifiers<M: Machine>(fixture: &Fixture, check: &AmplifierCheck) {
+    let name = fixture.path.display();
+    let mut amplifiers: Amplifiers<M> = Amplifiers::new(&fixture.program, check.phases.len());
+    let signal = if check.feedback {
+        amplifiers.feedback(&check.phases)
+    } else {
+        amplifiers.serial(&check.phases)
+    };
+    assert_eq!(
+        signal,
+        Some(check.max_signal),
+        "{} {:?}",
+        name,
+        check.phases
+    );
+    assert_eq!(
+        amplifiers.max_signal(&check.phases, check.feedback),
+        Some(check.max_signal),
+        "{}",
+        name
+    );
+}
+
+// every backend should be run through this
+pub fn check_conformance<M: Machine>() {
+    let fixtures = fixtures();
+    assert!(!fixtures.is_empty());
+    for fixture in fixtures.iter() {
+        // once on a fresh machine per run, then all of them again on one
+        // machine to make sure nothing leaks through a reset
+        for run in fixture.runs.iter() {
+            check_run(&mut M::new(fixture.program.clone()), fixture, run);
+        }
+        let mut machine = M::new(fixture.program.clone());
+        for run in fixture.runs.iter().chain(fixture.runs.iter()) {
+            check_run(&mut machine, fixture, run);
+        }
+        for check in fixture.amplifiers.iter() {
+            check_amplifiers::<M>(fixture, check);
+        }
+    }
+}
+
+#[test]
+fn test_interpreter_conformance() {
+    check_conformance::<Interpreter>();
+}
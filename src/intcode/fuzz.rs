@@ -0,0 +1,374 @@
+// Differential fuzzing of intcode machines. Random but well-formed programs
+// are run on a reference machine and on the machine under test, which have to
+// agree on the state they stop in, everything they output and their final
+// memory. Each machine is also driven a second way, reset from a dirty state
+// and fed its input only when it asks, so a single backend can be checked
+// against itself. Failing cases are shrunk before they are reported.
+
+use std::fmt;
+
+use super::{parameter_count, Machine, MachineState, ProgramData};
+
+// generated programs can loop forever, so every run gets a budget
+pub const STEP_LIMIT: usize = 1000;
+
+pub trait Entropy {
+    // a value in 0..bound
+    fn below(&mut self, bound: u32) -> u32;
+
+    fn range(&mut self, low: i32, high: i32) -> i32 {
+        low + self.below((high - low + 1) as u32) as i32
+    }
+
+    fn chance(&mut self, one_in: u32) -> bool {
+        self.below(one_in) == 0
+    }
+}
+
+// xorshift64*, so that `cargo test` always sees the same cases for a seed
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+}
+
+impl Entropy for Rng {
+    fn below(&mut self, bound: u32) -> u32 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        let value = self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 32;
+        (value % u64::from(bound)) as u32
+    }
+}
+
+// raw fuzzer input; once it runs out every choice is zero
+pub struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Bytes<'a> {
+    pub fn new(data: &'a [u8]) -> Bytes<'a> {
+        Bytes(data)
+    }
+}
+
+impl<'a> Entropy for Bytes<'a> {
+    fn below(&mut self, bound: u32) -> u32 {
+        match self.0.split_first() {
+            Some((&byte, rest)) => {
+                self.0 = rest;
+                u32::from(byte) % bound
+            }
+            None => 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Case {
+    pub program: ProgramData,
+    pub inputs: Vec<i32>,
+}
+
+pub fn generate<E: Entropy>(entropy: &mut E) -> Case {
+    const OPCODES: [i32; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+    let opcodes: Vec<i32> = (0..=entropy.below(12))
+        .map(|_| OPCODES[entropy.below(OPCODES.len() as u32) as usize])
+        .collect();
+
+    // lay the code out first so that addresses and jump targets are known
+    let mut starts: Vec<i32> = Vec::new();
+    let mut code_len = 0;
+    for &opcode in opcodes.iter() {
+        starts.push(code_len as i32);
+        code_len += 1 + parameter_count(opcode);
+    }
+    starts.push(code_len as i32); // the final halt
+    let data_start = code_len + 1;
+    let len = (data_start + 1 + entropy.below(6) as usize) as i32;
+
+    let mut program = ProgramData::new();
+    for &opcode in opcodes.iter() {
+        let at = program.len();
+        program.push(opcode);
+        for parameter in 1..=parameter_count(opcode) {
+            let writes = parameter == 3 || opcode == 3;
+            let immediate = !writes && entropy.chance(2);
+            let value = if (opcode == 5 || opcode == 6) && parameter == 2 && immediate {
+                starts[entropy.below(starts.len() as u32) as usize]
+            } else if immediate {
+                entropy.range(-10, 10)
+            } else if entropy.chance(4) {
+                // occasionally read or overwrite the code itself
+                entropy.range(0, len - 1)
+            } else {
+                entropy.range(data_start as i32, len - 1)
+            };
+            if immediate {
+                program[at] += 10i32.pow(parameter as u32 + 1);
+            }
+            program.push(value);
+        }
+    }
+    program.push(99);
+    while (program.len() as i32) < len {
+        let value = if entropy.chance(3) {
+            starts[entropy.below(starts.len() as u32) as usize]
+        } else {
+            entropy.range(-10, 10)
+        };
+        program.push(value);
+    }
+
+    let inputs = (0..entropy.below(4))
+        .map(|_| entropy.range(-10, 10))
+        .collect();
+    Case { program, inputs }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Outcome {
+    pub state: MachineState,
+    pub outputs: Vec<i32>,
+    pub memory: ProgramData,
+}
+
+// a fresh machine with every input queued up front
+pub fn execute<M: Machine>(case: &Case) -> Outcome {
+    let mut machine = M::new(case.program.clone());
+    machine.set_step_limit(Some(STEP_LIMIT));
+    let (state, outputs) = machine.run_with_inputs(&case.inputs);
+    Outcome {
+        state,
+        outputs,
+        memory: machine.memory().to_vec(),
+    }
+}
+
+// a machine that has already been run once, reset, and then handed each input
+// only when it stops to ask for one
+pub fn execute_resumed<M: Machine>(case: &Case) -> Outcome {
+    let mut machine = M::new(case.program.clone());
+    machine.set_step_limit(Some(STEP_LIMIT));
+    let reversed: Vec<i32> = case.inputs.iter().rev().cloned().collect();
+    machine.run_with_inputs(&reversed);
+    machine.reset();
+
+    let mut inputs = case.inputs.iter();
+    let mut outputs = Vec::new();
+    let state = loop {
+        match machine.run() {
+            MachineState::Output => outputs.extend(machine.output()),
+            MachineState::Input => match inputs.next() {
+                Some(&value) => machine.input(value),
+                None => break MachineState::Input,
+            },
+            state => break state,
+        }
+    };
+    Outcome {
+        state,
+        outputs,
+        memory: machine.memory().to_vec(),
+    }
+}
+
+#[derive(Debug)]
+pub struct Mismatch {
+    pub case: Case,
+    pub expected: Outcome,
+    pub actual: Outcome,
+}
+
+fn join(values: &[i32]) -> String {
+    values
+        .iter()
+        .map(i32::to_string)
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "program: {}", join(&self.case.program))?;
+        writeln!(f, "input: {}", join(&self.case.inputs))?;
+        writeln!(
+            f,
+            "expected: {:?}, output {}, memory {}",
+            self.expected.state,
+            join(&self.expected.outputs),
+            join(&self.expected.memory)
+        )?;
+        write!(
+            f,
+            "actual: {:?}, output {}, memory {}",
+            self.actual.state,
+            join(&self.actual.outputs),
+            join(&self.actual.memory)
+        )
+    }
+}
+
+// `A` is the reference, `B` the machine under test
+pub fn check<A: Machine, B: Machine>(case: &Case) -> Result<(), Box<Mismatch>> {
+    let expected = execute::<A>(case);
+    let others = vec![
+        execute_resumed::<A>(case),
+        execute::<B>(case),
+        execute_resumed::<B>(case),
+    ];
+    match others.into_iter().find(|actual| actual != &expected) {
+        Some(actual) => Err(Box::new(Mismatch {
+            case: case.clone(),
+            expected,
+            actual,
+        })),
+        None => Ok(()),
+    }
+}
+
+fn smaller(value: i32) -> Vec<i32> {
+    let mut candidates = vec![0, value / 2];
+    if value < 0 {
+        candidates.push(-value);
+    }
+    candidates.retain(|&c| c != value);
+    candidates
+}
+
+fn candidates(case: &Case) -> Vec<Case> {
+    let mut candidates = Vec::new();
+    for i in 0..case.inputs.len() {
+        let mut inputs = case.inputs.clone();
+        inputs.remove(i);
+        candidates.push(Case {
+            inputs,
+            ..case.clone()
+        });
+    }
+    for i in 0..case.program.len() {
+        let mut program = case.program.clone();
+        program.remove(i);
+        candidates.push(Case {
+            program,
+            ..case.clone()
+        });
+    }
+    for i in 0..case.program.len() {
+        for value in smaller(case.program[i]) {
+            let mut program = case.program.clone();
+            program[i] = value;
+            candidates.push(Case {
+                program,
+                ..case.clone()
+            });
+        }
+    }
+    for i in 0..case.inputs.len() {
+        for value in smaller(case.inputs[i]) {
+            let mut inputs = case.inputs.clone();
+            inputs[i] = value;
+            candidates.push(Case {
+                inputs,
+                ..case.clone()
+            });
+        }
+    }
+    candidates
+}
+
+// greedily take the first smaller case that still fails until none do; every
+// candidate is shorter or closer to zero, so this always terminates
+pub fn shrink<F: Fn(&Case) -> bool>(case: Case, fails: F) -> Case {
+    let mut case = case;
+    while let Some(smaller) = candidates(&case).into_iter().find(|c| fails(c)) {
+        case = smaller;
+    }
+    case
+}
+
+pub fn minimize<A: Machine, B: Machine>(mismatch: &Mismatch) -> Box<Mismatch> {
+    let case = shrink(mismatch.case.clone(), |c| check::<A, B>(c).is_err());
+    check::<A, B>(&case).unwrap_err()
+}
+
+pub fn differential<A: Machine, B: Machine>(seed: u64, cases: usize) -> Result<(), Box<Mismatch>> {
+    let mut rng = Rng::new(seed);
+    for _ in 0..cases {
+        let case = generate(&mut rng);
+        check::<A, B>(&case).map_err(|m| minimize::<A, B>(&m))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Interpreter;
+    use super::*;
+
+    #[test]
+    fn test_interpreter_differential() {
+        // there's no optimized backend yet, but this still holds the
+        // interpreter's fresh and resumed runs to each other
+        if let Err(mismatch) = differential::<Interpreter, Interpreter>(2019, 2000) {
+            panic!("\n{}", mismatch);
+        }
+    }
+
+    #[test]
+    fn test_generated_programs_are_well_formed() {
+        let mut rng = Rng::new(7);
+        let mut finished = 0;
+        for _ in 0..500 {
+            let case = generate(&mut rng);
+            if execute::<Interpreter>(&case).state == MachineState::Finished {
+                finished += 1;
+            }
+        }
+        // the rest run out of input, loop or trample their own code, but a
+        // good share should get all the way to their halt
+        assert!(finished > 150, "only {} of 500 finished", finished);
+    }
+
+    // a deliberately broken backend, to show failures are found and shrunk
+    struct OffByOne(Interpreter);
+
+    impl Machine for OffByOne {
+        fn new(image: ProgramData) -> OffByOne {
+            OffByOne(Interpreter::new(image))
+        }
+        fn step(&mut self) -> Option<MachineState> {
+            self.0.step()
+        }
+        fn reset(&mut self) {
+            self.0.reset()
+        }
+        fn input(&mut self, value: i32) {
+            self.0.input(value)
+        }
+        fn output(&mut self) -> Option<i32> {
+            self.0.output().map(|v| if v > 5 { v + 1 } else { v })
+        }
+        fn memory(&self) -> &[i32] {
+            self.0.memory()
+        }
+        fn instruction_pointer(&self) -> usize {
+            self.0.instruction_pointer()
+        }
+        fn steps(&self) -> usize {
+            self.0.steps()
+        }
+        fn set_step_limit(&mut self, limit: Option<usize>) {
+            self.0.set_step_limit(limit)
+        }
+    }
+
+    #[test]
+    fn test_mismatches_are_shrunk() {
+        let mismatch = differential::<Interpreter, OffByOne>(2019, 2000).unwrap_err();
+        assert!(mismatch.case.program.len() <= 4, "\n{}", mismatch);
+        assert!(mismatch.case.inputs.is_empty(), "\n{}", mismatch);
+        assert!(mismatch.actual.outputs.iter().any(|&v| v > 6));
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+// a set of address to value overrides for a program, e.g. the noun and verb
+// that day 2 pokes into addresses 1 and 2
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Patch {
+    cells: BTreeMap<usize, i32>,
+}
+
+#[derive(Debug)]
+pub enum PatchError {
+    Io(io::Error),
+    // a line that isn't `address=value`, numbered from 1
+    Syntax(usize, String),
+    // an address past the end of the program being patched
+    OutOfRange(usize),
+}
+
+impl From<io::Error> for PatchError {
+    fn from(e: io::Error) -> Self {
+        PatchError::Io(e)
+    }
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatchError::Io(e) => write!(f, "{}", e),
+            PatchError::Syntax(line, text) => {
+                write!(f, "line {}: expected address=value, got {:?}", line, text)
+            }
+            PatchError::OutOfRange(address) => {
+                write!(f, "address {} is outside the program", address)
+            }
+        }
+    }
+}
+
+impl Patch {
+    pub fn new() -> Patch {
+        Patch::default()
+    }
+
+    // later settings for the same address win
+    pub fn set(&mut self, address: usize, value: i32) -> &mut Patch {
+        self.cells.insert(address, value);
+        self
+    }
+
+    // a single `address=value` entry, as given to `--set`
+    pub fn set_from_str(&mut self, s: &str) -> Result<&mut Patch, PatchError> {
+        let (address, value) = parse_entry(s).ok_or_else(|| PatchError::Syntax(1, s.into()))?;
+        Ok(self.set(address, value))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, i32)> + '_ {
+        self.cells.iter().map(|(&a, &v)| (a, v))
+    }
+
+    // write the overrides into `program`, returning the patch that puts the
+    // original values back; nothing is written if any address is out of range
+    pub fn apply(&self, program: &mut [i32]) -> Result<Patch, PatchError> {
+        if let Some(address) = self.cells.keys().find(|&&a| a >= program.len()) {
+            return Err(PatchError::OutOfRange(*address));
+        }
+        let mut revert = Patch::new();
+        for (address, value) in self.iter() {
+            revert.set(address, program[address]);
+            program[address] = value;
+        }
+        Ok(revert)
+    }
+
+    // one `address=value` per line; blank lines and `#` comments are skipped
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Patch, PatchError> {
+        Patch::from_str(&fs::read_to_string(path)?)
+    }
+}
+
+fn parse_entry(s: &str) -> Option<(usize, i32)> {
+    let sep = s.find('=')?;
+    let address = usize::from_str(s[0..sep].trim()).ok()?;
+    let value = i32::from_str(s[(sep + 1)..].trim()).ok()?;
+    Some((address, value))
+}
+
+impl FromStr for Patch {
+    type Err = PatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut patch = Patch::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = match line.find('#') {
+                Some(comment) => &line[0..comment],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (address, value) =
+                parse_entry(line).ok_or_else(|| PatchError::Syntax(i + 1, line.into()))?;
+            patch.set(address, value);
+        }
+        Ok(patch)
+    }
+}
+
+impl fmt::Display for Patch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (address, value) in self.iter() {
+            writeln!(f, "{}={}", address, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_and_revert() {
+        let original = vec![1, 0, 0, 0, 99];
+        let mut program = original.clone();
+        let mut patch = Patch::new();
+        patch.set(1, 4).set(2, 4);
+        let revert = patch.apply(&mut program).unwrap();
+        assert_eq!(program, vec![1, 4, 4, 0, 99]);
+        assert_eq!(revert.to_string(), "1=0\n2=0\n");
+        revert.apply(&mut program).unwrap();
+        assert_eq!(program, original);
+    }
+
+    #[test]
+    fn test_out_of_range_leaves_program_alone() {
+        let mut program = vec![1, 0, 0, 0, 99];
+        let patch = Patch::from_str("1=12\n5=2").unwrap();
+        match patch.apply(&mut program) {
+            Err(PatchError::OutOfRange(5)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(program, vec![1, 0, 0, 0, 99]);
+    }
+
+    #[test]
+    fn test_parse() {
+        let patch = Patch::from_str("# noun and verb\n1 = 12\n\n2=2 # verb\n1=13\n").unwrap();
+        assert_eq!(patch.iter().collect::<Vec<_>>(), vec![(1, 13), (2, 2)]);
+        match Patch::from_str("1=12\n2:2") {
+            Err(PatchError::Syntax(2, text)) => assert_eq!(text, "2:2"),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(Patch::new().set_from_str("-1=3").is_err());
+    }
+
+    #[test]
+    fn test_load_day2_alarm() {
+        let alarm = Patch::load("data/day2.patch").unwrap();
+        assert_eq!(alarm.iter().collect::<Vec<_>>(), vec![(1, 12), (2, 2)]);
+        let mut program = crate::intcode::load("data/day2.txt").unwrap();
+        alarm.apply(&mut program).unwrap();
+        assert_eq!(program[1..3], [12, 2]);
+    }
+}
@@ -0,0 +1,420 @@
+// Recording and replaying the I/O of a run. A session file has one event per
+// line, each starting with the step (the number of instructions executed
+// before it) at which it happened, after the step limit the run had, if any:
+//
+//     step-limit 100
+//     0 in 5
+//     13 out 773660
+//     15 end finished
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::{Fault, Machine, MachineState, ProgramData};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Event {
+    Input(i32),
+    Output(i32),
+    // the state the machine stopped in for good
+    End(MachineState),
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Session {
+    pub events: Vec<(usize, Event)>,
+    // the step limit the machine was recorded with
+    pub step_limit: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum SessionError {
+    Io(io::Error),
+    // a line that isn't an event, numbered from 1
+    Syntax(usize, String),
+}
+
+impl From<io::Error> for SessionError {
+    fn from(e: io::Error) -> Self {
+        SessionError::Io(e)
+    }
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::Io(e) => write!(f, "{}", e),
+            SessionError::Syntax(line, text) => write!(f, "line {}: bad event {:?}", line, text),
+        }
+    }
+}
+
+fn format_state(state: &MachineState) -> String {
+    match state {
+        MachineState::Output => "output".to_string(),
+        MachineState::Input => "input".to_string(),
+        MachineState::Finished => "finished".to_string(),
+        MachineState::Error(Fault::InvalidOpcode(i)) => format!("invalid-opcode {}", i),
+        MachineState::Error(Fault::InvalidAddress(a)) => format!("invalid-address {}", a),
+        MachineState::Error(Fault::Overflow) => "overflow".to_string(),
+        MachineState::Error(Fault::StepLimit) => "step-limit".to_string(),
+    }
+}
+
+fn parse_state(words: &[&str]) -> Option<MachineState> {
+    let value = || words.get(1).and_then(|w| i32::from_str(w).ok());
+    match *words.first()? {
+        "output" => Some(MachineState::Output),
+        "input" => Some(MachineState::Input),
+        "finished" => Some(MachineState::Finished),
+        "invalid-opcode" => Some(MachineState::Error(Fault::InvalidOpcode(value()?))),
+        "invalid-address" => Some(MachineState::Error(Fault::InvalidAddress(value()?))),
+        "overflow" => Some(MachineState::Error(Fault::Overflow)),
+        "step-limit" => Some(MachineState::Error(Fault::StepLimit)),
+        _ => None,
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::Input(value) => write!(f, "in {}", value),
+            Event::Output(value) => write!(f, "out {}", value),
+            Event::End(state) => write!(f, "end {}", format_state(state)),
+        }
+    }
+}
+
+fn parse_event(line: &str) -> Option<(usize, Event)> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let step = usize::from_str(words.first()?).ok()?;
+    let value = || words.get(2).and_then(|w| i32::from_str(w).ok());
+    let event = match *words.get(1)? {
+        "in" => Event::Input(value()?),
+        "out" => Event::Output(value()?),
+        "end" => Event::End(parse_state(&words[2..])?),
+        _ => return None,
+    };
+    Some((step, event))
+}
+
+impl FromStr for Session {
+    type Err = SessionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut session = Session::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(limit) = line.strip_prefix("step-limit ") {
+                let limit = usize::from_str(limit.trim())
+                    .map_err(|_| SessionError::Syntax(i + 1, line.into()))?;
+                session.step_limit = Some(limit);
+                continue;
+            }
+            let event =
+                parse_event(line).ok_or_else(|| SessionError::Syntax(i + 1, line.into()))?;
+            session.events.push(event);
+        }
+        Ok(session)
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(limit) = self.step_limit {
+            writeln!(f, "step-limit {}", limit)?;
+        }
+        for (step, event) in self.events.iter() {
+            writeln!(f, "{} {}", step, event)?;
+        }
+        Ok(())
+    }
+}
+
+impl Session {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Session, SessionError> {
+        Session::from_str(&fs::read_to_string(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn inputs(&self) -> impl Iterator<Item = i32> + '_ {
+        self.events.iter().filter_map(|(_, event)| match event {
+            Event::Input(value) => Some(*value),
+            _ => None,
+        })
+    }
+}
+
+// wraps any machine, noting every input as it is consumed and every output as
+// it is produced; a reset starts a new session
+pub struct Recorder<M: Machine> {
+    machine: M,
+    // the inputs handed to the machine that it hasn't read yet
+    pending: VecDeque<i32>,
+    outputs: VecDeque<i32>,
+    session: Session,
+}
+
+impl<M: Machine> Recorder<M> {
+    pub fn wrap(machine: M) -> Recorder<M> {
+        Recorder {
+            machine,
+            pending: VecDeque::new(),
+            outputs: VecDeque::new(),
+            session: Session::default(),
+        }
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    pub fn machine(&self) -> &M {
+        &self.machine
+    }
+}
+
+impl<M: Machine> Machine for Recorder<M> {
+    fn new(image: ProgramData) -> Recorder<M> {
+        Recorder::wrap(M::new(image))
+    }
+
+    fn step(&mut self) -> Option<MachineState> {
+        let step = self.machine.steps();
+        let reads = self
+            .machine
+            .memory()
+            .get(self.machine.instruction_pointer())
+            .is_some_and(|instruction| instruction % 100 == 3);
+        let state = self.machine.step();
+        if reads && self.machine.steps() > step {
+            let value = self.pending.pop_front().unwrap();
+            self.session.events.push((step, Event::Input(value)));
+        }
+        match state {
+            Some(MachineState::Output) => {
+                while let Some(value) = self.machine.output() {
+                    self.session.events.push((step, Event::Output(value)));
+                    self.outputs.push_back(value);
+                }
+            }
+            Some(MachineState::Input) | None => {}
+            Some(state) => {
+                // don't note the same ending twice if the machine is poked again
+                if self.session.events.last().map(|(_, e)| e) != Some(&Event::End(state)) {
+                    self.session
+                        .events
+                        .push((self.machine.steps(), Event::End(state)));
+                }
+            }
+        }
+        state
+    }
+
+    fn reset(&mut self) {
+        self.machine.reset();
+        self.pending.clear();
+        self.outputs.clear();
+        self.session.events.clear();
+    }
+
+    fn input(&mut self, value: i32) {
+        self.pending.push_back(value);
+        self.machine.input(value);
+    }
+
+    fn output(&mut self) -> Option<i32> {
+        self.outputs.pop_front()
+    }
+
+    fn memory(&self) -> &[i32] {
+        self.machine.memory()
+    }
+
+    fn instruction_pointer(&self) -> usize {
+        self.machine.instruction_pointer()
+    }
+
+    fn steps(&self) -> usize {
+        self.machine.steps()
+    }
+
+    fn set_step_limit(&mut self, limit: Option<usize>) {
+        self.machine.set_step_limit(limit);
+        self.session.step_limit = limit;
+    }
+}
+
+// where a replay first stopped matching its recording; either side is `None`
+// if that run had already ended
+#[derive(Debug, Eq, PartialEq)]
+pub struct Divergence {
+    pub step: usize,
+    pub expected: Option<Event>,
+    pub actual: Option<Event>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let describe = |event: &Option<Event>| match event {
+            Some(event) => event.to_string(),
+            None => "nothing".to_string(),
+        };
+        write!(
+            f,
+            "diverged at step {}: expected {}, got {}",
+            self.step,
+            describe(&self.expected),
+            describe(&self.actual)
+        )
+    }
+}
+
+// the same event at the same step, except that the machine only has to end in
+// the same state, fault and all, at whatever step it gets there
+fn same((a_step, a): &(usize, Event), (b_step, b): &(usize, Event)) -> bool {
+    match (a, b) {
+        (Event::End(a), Event::End(b)) => a == b,
+        _ => a_step == b_step && a == b,
+    }
+}
+
+// run `program` again, feeding it the recorded inputs, and check that it does
+// exactly what it did before; the replay has the recording's step limit, or
+// without one is never allowed to run for longer than the recording did
+pub fn replay<M: Machine>(program: ProgramData, session: &Session) -> Result<Session, Divergence> {
+    let mut recorder: Recorder<M> = Recorder::new(program);
+    let last_step = session.events.last().map_or(0, |(step, _)| *step);
+    recorder.set_step_limit(session.step_limit.or(Some(last_step + 1)));
+    let mut inputs = session.inputs();
+    loop {
+        match recorder.run() {
+            MachineState::Output => while recorder.output().is_some() {},
+            MachineState::Input => match inputs.next() {
+                Some(value) => recorder.input(value),
+                None => break,
+            },
+            _ => break,
+        }
+    }
+
+    let actual = &recorder.session().events;
+    let expected = &session.events;
+    for i in 0..expected.len().max(actual.len()) {
+        let matching = match (expected.get(i), actual.get(i)) {
+            (Some(a), Some(b)) => same(a, b),
+            _ => false,
+        };
+        if !matching {
+            let step = match (expected.get(i), actual.get(i)) {
+                (Some((a, _)), Some((b, _))) => *a.min(b),
+                (Some((a, _)), None) | (None, Some((a, _))) => *a,
+                (None, None) => unreachable!(),
+            };
+            return Err(Divergence {
+                step,
+                expected: expected.get(i).map(|(_, e)| *e),
+                actual: actual.get(i).map(|(_, e)| *e),
+            });
+        }
+    }
+    let mut replayed = recorder.session().clone();
+    replayed.step_limit = session.step_limit;
+    Ok(replayed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Interpreter;
+    use super::*;
+
+    // outputs 1 if the input is 8, otherwise 0
+    fn program() -> ProgramData {
+        vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]
+    }
+
+    #[test]
+    fn test_record() {
+        let mut recorder: Recorder<Interpreter> = Recorder::new(program());
+        assert_eq!(recorder.run(), MachineState::Input);
+        recorder.input(8);
+        assert_eq!(recorder.run(), MachineState::Output);
+        assert_eq!(recorder.output(), Some(1));
+        assert_eq!(recorder.run(), MachineState::Finished);
+        assert_eq!(
+            recorder.session().to_string(),
+            "0 in 8\n2 out 1\n3 end finished\n"
+        );
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let text = "# a comment\n0 in 8\n2 out 1\n3 end invalid-opcode 42\n";
+        let session = Session::from_str(text).unwrap();
+        assert_eq!(
+            session.events[2],
+            (3, Event::End(MachineState::Error(Fault::InvalidOpcode(42))))
+        );
+        assert_eq!(session.to_string(), &text[12..]);
+        match Session::from_str("0 in 8\n2 put 1\n") {
+            Err(SessionError::Syntax(2, _)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut recorder: Recorder<Interpreter> = Recorder::new(program());
+        recorder.run_with_inputs(&[8]);
+        let session = recorder.session().clone();
+        assert_eq!(
+            replay::<Interpreter>(program(), &session),
+            Ok(session.clone())
+        );
+
+        // the same inputs against a program that compares with 7 instead
+        let mut changed = program();
+        changed[10] = 7;
+        assert_eq!(
+            replay::<Interpreter>(changed, &session),
+            Err(Divergence {
+                step: 2,
+                expected: Some(Event::Output(1)),
+                actual: Some(Event::Output(0)),
+            })
+        );
+    }
+
+    #[test]
+    fn test_replay_to_the_step_limit() {
+        // loops forever
+        let forever = vec![1105, 1, 0];
+        let mut recorder: Recorder<Interpreter> = Recorder::new(forever.clone());
+        recorder.set_step_limit(Some(10));
+        assert_eq!(recorder.run(), MachineState::Error(Fault::StepLimit));
+        let text = recorder.session().to_string();
+        assert_eq!(text, "step-limit 10\n10 end step-limit\n");
+
+        let session = Session::from_str(&text).unwrap();
+        assert_eq!(session.step_limit, Some(10));
+        assert_eq!(
+            replay::<Interpreter>(forever.clone(), &session),
+            Ok(session)
+        );
+
+        // a recording from before limits were saved still replays, even
+        // though its guard stops it a step later
+        let unlimited = Session::from_str("10 end step-limit\n").unwrap();
+        assert!(replay::<Interpreter>(forever, &unlimited).is_ok());
+    }
+}
@@ -0,0 +1,5 @@
+pub mod fuel;
+pub mod geometry;
+pub mod intcode;
+pub mod password;
+pub mod wires;
@@ -0,0 +1,370 @@
+// The password rules from day 4, and sets of them read from a small spec:
+// rules separated by `;` or new lines, each a name with an `=value` if it
+// takes one, for example `digits=6; nondecreasing; group_exact=2`. The same
+// spec can be written in the part of TOML that covers it: one `name = value`
+// a line, where a rule without a value is `name = true` and `name = false`
+// leaves it out. Values may be quoted, `#` starts a comment, and table
+// headers such as `[rules]` or `[password]` are skipped, so the rules can sit
+// in a bigger file; nothing else of TOML is understood.
+//
+//   digits=N           exactly N digits
+//   nondecreasing      no digit is smaller than the one before
+//   adjacent           at least two digits next to each other are the same
+//   group_exact=N      a run of exactly N of the same digit
+
+use std::convert::From;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+use std::str::FromStr;
+
+// the rules for each part of the puzzle
+pub const PART_ONE: &str = "digits=6; nondecreasing; adjacent";
+pub const PART_TWO: &str = "digits=6; nondecreasing; group_exact=2";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Password(Vec<i32>);
+
+impl From<i32> for Password {
+    fn from(item: i32) -> Self {
+        let mut digits: Vec<i32> = Vec::new();
+        let mut n = item;
+        while n > 9 {
+            digits.push(n % 10);
+            n /= 10;
+        }
+        digits.push(n);
+        digits.reverse();
+        Password(digits)
+    }
+}
+
+impl Deref for Password {
+    type Target = Vec<i32>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+pub trait PasswordRule {
+    fn validate(&self, input: &Password) -> bool;
+
+    // in the same form the spec takes
+    fn describe(&self) -> String;
+}
+
+pub struct NumDigits(pub usize);
+
+impl PasswordRule for NumDigits {
+    fn validate(&self, input: &Password) -> bool {
+        input.0.len() == self.0
+    }
+
+    fn describe(&self) -> String {
+        format!("digits={}", self.0)
+    }
+}
+
+pub struct TwoAdjacentIdenticalDigits;
+
+impl PasswordRule for TwoAdjacentIdenticalDigits {
+    fn validate(&self, input: &Password) -> bool {
+        let iter_a = input.iter();
+        let mut iter_b = input.iter();
+        iter_b.next();
+        for (a, b) in iter_a.zip(iter_b) {
+            if a == b {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn describe(&self) -> String {
+        "adjacent".to_string()
+    }
+}
+
+pub struct AlwaysIncreasing;
+
+impl PasswordRule for AlwaysIncreasing {
+    fn validate(&self, input: &Password) -> bool {
+        let iter_a = input.iter();
+        let mut iter_b = input.iter();
+        iter_b.next();
+        for (a, b) in iter_a.zip(iter_b) {
+            if b < a {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn describe(&self) -> String {
+        "nondecreasing".to_string()
+    }
+}
+
+pub struct AtLeastOneGroupOfLength(pub usize);
+
+impl AtLeastOneGroupOfLength {
+    fn groups(input: &Password) -> Vec<Vec<i32>> {
+        let mut groups: Vec<Vec<i32>> = Vec::new();
+        let mut group: Vec<i32> = Vec::new();
+        for a in input.iter() {
+            if !group.is_empty() && a != group.last().unwrap() {
+                groups.push(group);
+                group = Vec::new();
+            }
+            group.push(*a);
+        }
+        groups.push(group);
+        groups
+    }
+}
+
+impl PasswordRule for AtLeastOneGroupOfLength {
+    fn validate(&self, input: &Password) -> bool {
+        let groups = AtLeastOneGroupOfLength::groups(input);
+        groups.iter().any(|g| g.len() == self.0)
+    }
+
+    fn describe(&self) -> String {
+        format!("group_exact={}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub enum SpecError {
+    Io(io::Error),
+    UnknownRule(String),
+    // a rule that needs a number, and what it was given instead, if anything
+    BadValue(String, Option<String>),
+    // a rule that's only on or off, given something other than true or false
+    NotASwitch(String, String),
+}
+
+impl From<io::Error> for SpecError {
+    fn from(e: io::Error) -> Self {
+        SpecError::Io(e)
+    }
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpecError::Io(e) => write!(f, "{}", e),
+            SpecError::UnknownRule(name) => write!(f, "unknown rule {:?}", name),
+            SpecError::BadValue(name, None) => write!(f, "{} needs a number", name),
+            SpecError::BadValue(name, Some(value)) => {
+                write!(f, "{} needs a number, not {:?}", name, value)
+            }
+            SpecError::NotASwitch(name, value) => {
+                write!(f, "{} is true or false, not {:?}", name, value)
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct RuleSet {
+    pub rules: Vec<Box<dyn PasswordRule>>,
+}
+
+impl RuleSet {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<RuleSet, SpecError> {
+        RuleSet::from_str(&fs::read_to_string(path)?)
+    }
+
+    pub fn validate(&self, input: &Password) -> bool {
+        self.rules.iter().all(|r| r.validate(input))
+    }
+
+    // the rules `input` breaks, in the order they were given
+    pub fn rejections(&self, input: &Password) -> Vec<&dyn PasswordRule> {
+        self.rules
+            .iter()
+            .filter(|r| !r.validate(input))
+            .map(|r| r.as_ref())
+            .collect()
+    }
+
+    // in the same form the spec takes
+    pub fn describe(&self) -> String {
+        let rules: Vec<String> = self.rules.iter().map(|r| r.describe()).collect();
+        rules.join("; ")
+    }
+}
+
+fn number(name: &str, value: Option<&str>) -> Result<usize, SpecError> {
+    let bad = || SpecError::BadValue(name.into(), value.map(String::from));
+    usize::from_str(value.ok_or_else(bad)?).map_err(|_| bad())
+}
+
+// a TOML table header, `[name]`
+fn is_header(entry: &str) -> bool {
+    entry.starts_with('[') && entry.ends_with(']')
+}
+
+// a value without the quotes round it, if it has them
+fn unquote(value: &str) -> &str {
+    for &quote in ['"', '\''].iter() {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
+}
+
+impl FromStr for RuleSet {
+    type Err = SpecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut set = RuleSet::default();
+        for entry in s
+            .lines()
+            .map(|line| line.split('#').next().unwrap())
+            .flat_map(|line| line.split(';'))
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty() && !is_header(entry))
+        {
+            let (name, value) = match entry.find('=') {
+                Some(equals) => (
+                    entry[..equals].trim(),
+                    Some(unquote(entry[equals + 1..].trim())),
+                ),
+                None => (entry, None),
+            };
+            let rule: Box<dyn PasswordRule> = match (name, value) {
+                ("digits", _) => Box::new(NumDigits(number(name, value)?)),
+                ("group_exact", _) => Box::new(AtLeastOneGroupOfLength(number(name, value)?)),
+                ("nondecreasing", None | Some("true")) => Box::new(AlwaysIncreasing),
+                ("adjacent", None | Some("true")) => Box::new(TwoAdjacentIdenticalDigits),
+                ("nondecreasing" | "adjacent", Some("false")) => continue,
+                ("nondecreasing" | "adjacent", Some(value)) => {
+                    return Err(SpecError::NotASwitch(name.into(), value.into()))
+                }
+                _ => return Err(SpecError::UnknownRule(name.into())),
+            };
+            set.rules.push(rule);
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_from() {
+        assert_eq!(Password::from(1234).0, vec![1i32, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_sixdigits() {
+        let six_digits = NumDigits(6);
+        assert!(six_digits.validate(&123456.into()));
+        assert!(!six_digits.validate(&12345.into()));
+        assert!(!six_digits.validate(&1234567891.into()));
+        assert!(NumDigits(10).validate(&1234567891.into()));
+    }
+
+    #[test]
+    fn test_two_adjacent() {
+        assert!(TwoAdjacentIdenticalDigits {}.validate(&1223.into()));
+        assert!(!TwoAdjacentIdenticalDigits {}.validate(&1234.into()));
+    }
+
+    #[test]
+    fn test_always_increasing() {
+        let rule = AlwaysIncreasing {};
+        assert!(rule.validate(&1234.into()));
+        assert!(!rule.validate(&4321.into()));
+        assert!(rule.validate(&1233.into()));
+        assert!(!rule.validate(&1232.into()));
+    }
+
+    #[test]
+    fn test_groups() {
+        assert_eq!(
+            AtLeastOneGroupOfLength::groups(&1234.into()),
+            vec![vec![1i32], vec![2], vec![3], vec![4]]
+        );
+    }
+
+    #[test]
+    fn test_at_least_one_group_of_length() {
+        let two = AtLeastOneGroupOfLength(2);
+        let five = AtLeastOneGroupOfLength(5);
+
+        assert!(two.validate(&1223.into()));
+        assert!(!two.validate(&1234.into()));
+        assert!(two.validate(&112233.into()));
+        assert!(!two.validate(&123444.into()));
+        assert!(two.validate(&111122.into()));
+
+        assert!(five.validate(&1222223.into()));
+        assert!(!five.validate(&12222223.into()));
+    }
+
+    #[test]
+    fn test_spec() {
+        let part_two = RuleSet::from_str(PART_TWO).unwrap();
+        assert_eq!(part_two.describe(), PART_TWO);
+        assert!(part_two.validate(&112233.into()));
+        let rejected: Vec<String> = part_two
+            .rejections(&123444.into())
+            .iter()
+            .map(|r| r.describe())
+            .collect();
+        assert_eq!(rejected, vec!["group_exact=2"]);
+        let rejected: Vec<String> = part_two
+            .rejections(&12321.into())
+            .iter()
+            .map(|r| r.describe())
+            .collect();
+        assert_eq!(rejected, vec!["digits=6", "nondecreasing", "group_exact=2"]);
+    }
+
+    #[test]
+    fn test_toml_spec() {
+        let toml = "# part one
+[rules]
+digits = 6
+nondecreasing = true
+adjacent = true
+group_exact = 2
+";
+        assert_eq!(
+            RuleSet::from_str(toml).unwrap().describe(),
+            "digits=6; nondecreasing; adjacent; group_exact=2"
+        );
+        let off = RuleSet::from_str("digits = 6\nadjacent = false").unwrap();
+        assert_eq!(off.describe(), "digits=6");
+        let quoted = "[password]
+digits = \"6\"  # six of them
+group_exact = '2'
+";
+        assert_eq!(
+            RuleSet::from_str(quoted).unwrap().describe(),
+            "digits=6; group_exact=2"
+        );
+    }
+
+    #[test]
+    fn test_spec_errors() {
+        let error = |s: &str| RuleSet::from_str(s).err().unwrap().to_string();
+        assert_eq!(error("digits=6; palindrome"), "unknown rule \"palindrome\"");
+        assert_eq!(error("digits"), "digits needs a number");
+        assert_eq!(
+            error("group_exact=two"),
+            "group_exact needs a number, not \"two\""
+        );
+        assert_eq!(error("adjacent=2"), "adjacent is true or false, not \"2\"");
+        assert!(RuleSet::from_str("").unwrap().rules.is_empty());
+    }
+}
@@ -0,0 +1,827 @@
+// Wires from day 3, stored as the straight segments they're made of rather
+// than every point they pass through, so that long wires stay cheap.
+// Crossings between two wires are found with a sweep along x: horizontal
+// segments of one wire are live while the sweep is over them, and each
+// vertical segment of the other asks which of those lie within its span.
+// Segments of both wires along the same line are swept along it, and share a
+// run of points, which is kept as its two ends so that a long run costs no
+// more than a single crossing until its points are asked for. Diagonal
+// segments are rare enough to get a simpler sweep, against every segment of
+// the other wire whose x-extent overlaps theirs.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+pub use crate::geometry::Point;
+use crate::geometry::Vector;
+
+pub mod render;
+
+// the diagonals are written NE, NW, SE and SW
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpRight,
+    UpLeft,
+    DownRight,
+    DownLeft,
+}
+
+impl Direction {
+    // a single step
+    pub fn delta(&self) -> Vector {
+        match self {
+            Self::Up => Vector::UP,
+            Self::Down => Vector::DOWN,
+            Self::Left => Vector::LEFT,
+            Self::Right => Vector::RIGHT,
+            Self::UpRight => Vector::UP + Vector::RIGHT,
+            Self::UpLeft => Vector::UP + Vector::LEFT,
+            Self::DownRight => Vector::DOWN + Vector::RIGHT,
+            Self::DownLeft => Vector::DOWN + Vector::LEFT,
+        }
+    }
+
+    pub fn step(&self, p: Point) -> Point {
+        self.go(p, 1)
+    }
+
+    pub fn go(&self, p: Point, distance: i32) -> Point {
+        p + self.delta() * distance
+    }
+}
+
+impl FromStr for Direction {
+    type Err = InstructionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "U" => Ok(Direction::Up),
+            "D" => Ok(Direction::Down),
+            "L" => Ok(Direction::Left),
+            "R" => Ok(Direction::Right),
+            "NE" => Ok(Direction::UpRight),
+            "NW" => Ok(Direction::UpLeft),
+            "SE" => Ok(Direction::DownRight),
+            "SW" => Ok(Direction::DownLeft),
+            _ => Err(InstructionParseError::Direction(s.into())),
+        }
+    }
+}
+
+// a distance is a count of steps, so a diagonal move of 3 ends up 3 along and
+// 3 up or down
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Instruction {
+    pub direction: Direction,
+    pub distance: i32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InstructionParseError {
+    Empty,
+    // the letters at the front, which aren't a direction
+    Direction(String),
+    // whatever followed the direction, which isn't a whole number of steps
+    Distance(String),
+}
+
+impl fmt::Display for InstructionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InstructionParseError::Empty => write!(f, "empty instruction"),
+            InstructionParseError::Direction(s) => write!(f, "expected a direction, got {:?}", s),
+            InstructionParseError::Distance(s) => write!(f, "expected a distance, got {:?}", s),
+        }
+    }
+}
+
+// case doesn't matter, and there may be spaces around the instruction or
+// between its direction and distance
+impl FromStr for Instruction {
+    type Err = InstructionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(InstructionParseError::Empty);
+        }
+        let letters = s
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(s.len());
+        let direction = Direction::from_str(&s[..letters])?;
+        let rest = s[letters..].trim_start();
+        let bad = || InstructionParseError::Distance(rest.into());
+        if rest.is_empty() || !rest.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(bad());
+        }
+        let distance = i32::from_str(rest).map_err(|_| bad())?;
+        Ok(Instruction {
+            direction,
+            distance,
+        })
+    }
+}
+
+// an instruction that didn't parse, and where it was
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    // counting from 0, and not counting blank lines
+    pub wire: usize,
+    // which of the wire's instructions, from 0
+    pub token: usize,
+    // how many bytes into its line the instruction starts
+    pub offset: usize,
+    pub error: InstructionParseError,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "wire {}, instruction {} (byte {}): {}",
+            self.wire, self.token, self.offset, self.error
+        )
+    }
+}
+
+fn parse_wire(wire: usize, line: &str) -> Result<Vec<Instruction>, ParseError> {
+    let mut offset = 0;
+    let mut instructions = Vec::new();
+    for (token, text) in line.split(',').enumerate() {
+        let start = offset + (text.len() - text.trim_start().len());
+        offset += text.len() + 1;
+        let instruction = Instruction::from_str(text).map_err(|error| ParseError {
+            wire,
+            token,
+            offset: start,
+            error,
+        })?;
+        instructions.push(instruction);
+    }
+    Ok(instructions)
+}
+
+// one wire per line, as comma separated instructions
+pub fn parse(s: &str) -> Result<Vec<Vec<Instruction>>, ParseError> {
+    s.lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(wire, line)| parse_wire(wire, line))
+        .collect()
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Vec<Vec<Instruction>>> {
+    let text = fs::read_to_string(path)?;
+    parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Segment {
+    pub start: Point,
+    pub end: Point,
+    // how far along the wire `start` is
+    pub steps: u32,
+}
+
+impl Segment {
+    fn is_horizontal(&self) -> bool {
+        self.start.y == self.end.y
+    }
+
+    fn is_vertical(&self) -> bool {
+        self.start.x == self.end.x
+    }
+
+    fn is_diagonal(&self) -> bool {
+        !self.is_horizontal() && !self.is_vertical()
+    }
+
+    // each step along the segment
+    fn delta(&self) -> Vector {
+        (self.end - self.start).signum()
+    }
+
+    // how many steps it takes from `start` to `p`, which has to be on the
+    // segment; a diagonal step counts as one
+    fn moves_to(&self, p: Point) -> i32 {
+        self.start.chebyshev_to(p)
+    }
+
+    // the steps along the wire to `p`, which has to be on this segment
+    pub fn steps_to(&self, p: Point) -> u32 {
+        self.steps + self.moves_to(p) as u32
+    }
+
+    // the span along the segment's own axis, lowest first
+    fn span(&self) -> (i32, i32) {
+        let (a, b) = if self.is_horizontal() {
+            (self.start.x, self.end.x)
+        } else {
+            (self.start.y, self.end.y)
+        };
+        (a.min(b), a.max(b))
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Wire {
+    pub segments: Vec<Segment>,
+}
+
+impl Wire {
+    // zero-length moves leave no segment behind
+    pub fn new(instructions: &[Instruction]) -> Wire {
+        let mut segments = Vec::new();
+        let mut at = Point::ORIGIN;
+        let mut steps = 0;
+        for i in instructions.iter().filter(|i| i.distance != 0) {
+            let end = i.direction.go(at, i.distance);
+            segments.push(Segment {
+                start: at,
+                end,
+                steps,
+            });
+            steps += i.distance.unsigned_abs();
+            at = end;
+        }
+        Wire { segments }
+    }
+
+    pub fn length(&self) -> u32 {
+        self.segments.last().map_or(0, |s| s.steps_to(s.end))
+    }
+}
+
+// points where two wires meet in a line, from `start` to `end` inclusive; a
+// single crossing when the two are the same
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Run {
+    start: Point,
+    end: Point,
+}
+
+impl Run {
+    fn point(p: Point) -> Run {
+        Run { start: p, end: p }
+    }
+
+    fn points(self) -> impl Iterator<Item = Point> {
+        let step = (self.end - self.start).signum();
+        (0..=self.start.chebyshev_to(self.end)).map(move |k| self.start + step * k)
+    }
+
+    // the only points that can be the run's closest to the origin, or the
+    // fewest steps along two segments, leaving out the origin itself: both
+    // ends, and either side of where the run crosses x = 0 or y = 0, since
+    // the distance and the steps only change how fast they grow there
+    fn candidates(self) -> impl Iterator<Item = Point> {
+        let step = (self.end - self.start).signum();
+        let length = self.start.chebyshev_to(self.end);
+        let mut along = vec![0, length];
+        // how far along the run x, and then y, is zero
+        for &(at, by) in [(self.start.x, step.x), (self.start.y, step.y)].iter() {
+            if by != 0 {
+                let k = -at * by;
+                along.extend_from_slice(&[k - 1, k, k + 1]);
+            }
+        }
+        along
+            .into_iter()
+            .filter(move |k| (0..=length).contains(k))
+            .map(move |k| self.start + step * k)
+            .filter(|&p| p != Point::ORIGIN)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Crossing {
+    pub point: Point,
+    // the fewest steps each wire takes to get there
+    pub steps: (u32, u32),
+}
+
+impl Crossing {
+    pub fn total_steps(&self) -> u32 {
+        self.steps.0 + self.steps.1
+    }
+}
+
+// horizontal segments of one wire against vertical ones of the other
+fn sweep<F: FnMut(Run, &Segment, &Segment)>(
+    horizontal: &[&Segment],
+    vertical: &[&Segment],
+    mut cross: F,
+) {
+    // at the same x, segments start before they're asked about and end after
+    let mut events: Vec<(i32, u8, usize)> = Vec::new();
+    for (i, h) in horizontal.iter().enumerate() {
+        let (low, high) = h.span();
+        events.push((low, 0, i));
+        events.push((high, 2, i));
+    }
+    for (i, v) in vertical.iter().enumerate() {
+        events.push((v.start.x, 1, i));
+    }
+    events.sort_unstable();
+
+    let mut live: BTreeSet<(i32, usize)> = BTreeSet::new();
+    for (x, kind, i) in events {
+        match kind {
+            0 => {
+                live.insert((horizontal[i].start.y, i));
+            }
+            1 => {
+                let (low, high) = vertical[i].span();
+                for &(y, h) in live.range((low, 0)..=(high, usize::MAX)) {
+                    cross(Run::point(Point { x, y }), horizontal[h], vertical[i]);
+                }
+            }
+            _ => {
+                live.remove(&(horizontal[i].start.y, i));
+            }
+        }
+    }
+}
+
+// segments of both wires lying along the same line, swept along it; every
+// stretch they share is a run
+fn overlaps<F: FnMut(Run, &Segment, &Segment)>(a: &[&Segment], b: &[&Segment], mut cross: F) {
+    let line = |s: &Segment| {
+        (
+            s.is_horizontal(),
+            if s.is_horizontal() {
+                s.start.y
+            } else {
+                s.start.x
+            },
+        )
+    };
+    let mut starts: Vec<((bool, i32), i32, bool, &Segment)> = a
+        .iter()
+        .map(|&s| (line(s), s.span().0, true, s))
+        .chain(b.iter().map(|&t| (line(t), t.span().0, false, t)))
+        .collect();
+    starts.sort_by_key(|&(line, low, _, _)| (line, low));
+
+    // the segments of each side the sweep is still over, by where they end
+    let mut live: [BTreeSet<(i32, usize)>; 2] = [BTreeSet::new(), BTreeSet::new()];
+    for (i, &(on, low, from_a, s)) in starts.iter().enumerate() {
+        if i > 0 && starts[i - 1].0 != on {
+            live = [BTreeSet::new(), BTreeSet::new()];
+        }
+        for side in live.iter_mut() {
+            while side.first().is_some_and(|&(high, _)| high < low) {
+                side.pop_first();
+            }
+        }
+        let (mine, other) = if from_a { (0, 1) } else { (1, 0) };
+        for &(t_high, j) in live[other].iter() {
+            let t = starts[j].3;
+            let high = s.span().1.min(t_high);
+            let at = |along: i32| {
+                if s.is_horizontal() {
+                    Point::new(along, s.start.y)
+                } else {
+                    Point::new(s.start.x, along)
+                }
+            };
+            let run = Run {
+                start: at(low),
+                end: at(high),
+            };
+            if from_a {
+                cross(run, s, t);
+            } else {
+                cross(run, t, s);
+            }
+        }
+        live[mine].insert((s.span().1, i));
+    }
+}
+
+// where two segments meet when at least one of them is diagonal, by solving
+// for how many steps along each of them reach the same point
+fn meet<F: FnMut(Run, &Segment, &Segment)>(s: &Segment, t: &Segment, cross: &mut F) {
+    let (ds, dt) = (s.delta(), t.delta());
+    let r = t.start - s.start;
+    let (s_len, t_len) = (s.moves_to(s.end), t.moves_to(t.end));
+    let along_s = |k: i32| s.start + ds * k;
+
+    let det = dt.cross(ds);
+    if det != 0 {
+        // diagonals can cross between points, which isn't a crossing at all
+        let (k, m) = (dt.cross(r), ds.cross(r));
+        if k % det == 0 && m % det == 0 {
+            let (k, m) = (k / det, m / det);
+            if (0..=s_len).contains(&k) && (0..=t_len).contains(&m) {
+                cross(Run::point(along_s(k)), s, t);
+            }
+        }
+        return;
+    }
+    if ds.cross(r) != 0 {
+        // parallel, but on different lines
+        return;
+    }
+    // along the same line: find where `t` starts and ends in steps along `s`
+    let unit = ds.dot(ds);
+    let first = r.dot(ds) / unit;
+    let last = first + t_len * dt.dot(ds) / unit;
+    let (low, high) = (first.min(last).max(0), first.max(last).min(s_len));
+    if low <= high {
+        let run = Run {
+            start: along_s(low),
+            end: along_s(high),
+        };
+        cross(run, s, t);
+    }
+}
+
+// segments of `a`, which should be the diagonal ones, against those of `b`,
+// swept along x so that only segments whose x-extents overlap are compared.
+// That is every pair in the worst case, when all of them span the same x, but
+// `a` only ever holds diagonals, so the straight segments still go through
+// `sweep` and `overlaps`
+fn diagonals<F: FnMut(Run, &Segment, &Segment)>(a: &[&Segment], b: &[&Segment], mut cross: F) {
+    let extent = |s: &Segment| (s.start.x.min(s.end.x), s.start.x.max(s.end.x));
+    let mut starts: Vec<(i32, bool, &Segment)> = a
+        .iter()
+        .map(|&s| (extent(s).0, true, s))
+        .chain(b.iter().map(|&t| (extent(t).0, false, t)))
+        .collect();
+    starts.sort_by_key(|&(x, _, _)| x);
+
+    // the segments of each side the sweep is still over
+    let (mut live_a, mut live_b): (Vec<&Segment>, Vec<&Segment>) = (Vec::new(), Vec::new());
+    for (x, from_a, s) in starts {
+        live_a.retain(|&t| extent(t).1 >= x);
+        live_b.retain(|&t| extent(t).1 >= x);
+        if from_a {
+            for &t in live_b.iter() {
+                meet(s, t, &mut cross);
+            }
+            live_a.push(s);
+        } else {
+            for &t in live_a.iter() {
+                meet(t, s, &mut cross);
+            }
+            live_b.push(s);
+        }
+    }
+}
+
+fn split(wire: &Wire, keep: fn(&Segment) -> bool) -> Vec<&Segment> {
+    wire.segments.iter().filter(|&s| keep(s)).collect()
+}
+
+// every run of points the two wires share, with the segments of each that
+// share it
+fn runs<F: FnMut(Run, &Segment, &Segment)>(a: &Wire, b: &Wire, mut found: F) {
+    let (a_h, a_v) = (
+        split(a, Segment::is_horizontal),
+        split(a, Segment::is_vertical),
+    );
+    let (b_h, b_v) = (
+        split(b, Segment::is_horizontal),
+        split(b, Segment::is_vertical),
+    );
+    sweep(&a_h, &b_v, |run, on_a, on_b| found(run, on_a, on_b));
+    sweep(&b_h, &a_v, |run, on_b, on_a| found(run, on_a, on_b));
+    overlaps(&a_h, &b_h, |run, on_a, on_b| found(run, on_a, on_b));
+    overlaps(&a_v, &b_v, |run, on_a, on_b| found(run, on_a, on_b));
+
+    // diagonals of `a` against all of `b`, then diagonals of `b` against the
+    // rest of `a`
+    let (a_d, b_d) = (
+        split(a, Segment::is_diagonal),
+        split(b, Segment::is_diagonal),
+    );
+    let b_all: Vec<&Segment> = b.segments.iter().collect();
+    let a_straight: Vec<&Segment> = a_h.iter().chain(a_v.iter()).cloned().collect();
+    diagonals(&a_d, &b_all, |run, on_a, on_b| found(run, on_a, on_b));
+    diagonals(&b_d, &a_straight, |run, on_b, on_a| found(run, on_a, on_b));
+}
+
+// every point other than the origin that both wires pass through, closest to
+// the origin first; a run the wires share is listed point by point, so this
+// takes as long as the longest of them
+pub fn crossings(a: &Wire, b: &Wire) -> Vec<Crossing> {
+    let mut found: HashMap<Point, (u32, u32)> = HashMap::new();
+    runs(a, b, |run, on_a, on_b| {
+        for point in run.points().filter(|&p| p != Point::ORIGIN) {
+            let steps = (on_a.steps_to(point), on_b.steps_to(point));
+            let best = found.entry(point).or_insert(steps);
+            *best = (best.0.min(steps.0), best.1.min(steps.1));
+        }
+    });
+
+    let mut crossings: Vec<Crossing> = found
+        .into_iter()
+        .map(|(point, steps)| Crossing { point, steps })
+        .collect();
+    crossings.sort_by_key(|c| (c.point.manhattan(), c.point));
+    crossings
+}
+
+// the closest crossing and its distance, and the crossing with the fewest
+// steps and how many
+type Answers = (Option<(Point, i32)>, Option<(Point, u32)>);
+
+// both of the puzzle's answers for a pair of wires, looking only at the
+// candidates of each run rather than every point along it
+fn closest_pair(a: &Wire, b: &Wire) -> Answers {
+    let mut by_manhattan: Option<(i32, Point)> = None;
+    let mut by_steps: Option<(u32, i32, Point)> = None;
+    runs(a, b, |run, on_a, on_b| {
+        for point in run.candidates() {
+            let distance = (point.manhattan(), point);
+            if by_manhattan.is_none_or(|best| distance < best) {
+                by_manhattan = Some(distance);
+            }
+            let steps = (
+                on_a.steps_to(point) + on_b.steps_to(point),
+                point.manhattan(),
+                point,
+            );
+            if by_steps.is_none_or(|best| steps < best) {
+                by_steps = Some(steps);
+            }
+        }
+    });
+    (
+        by_manhattan.map(|(distance, point)| (point, distance)),
+        by_steps.map(|(steps, _, point)| (point, steps)),
+    )
+}
+
+// a point where several wires meet
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Junction {
+    pub point: Point,
+    // the index of each wire that passes through, with the fewest steps it
+    // takes to get there, in order of index
+    pub wires: Vec<(usize, u32)>,
+}
+
+impl Junction {
+    pub fn manhattan(&self) -> i32 {
+        self.point.manhattan()
+    }
+
+    pub fn total_steps(&self) -> u32 {
+        self.wires.iter().map(|&(_, steps)| steps).sum()
+    }
+}
+
+// every point other than the origin where at least `k` of the wires cross,
+// closest to the origin first; each pair of wires is swept separately
+pub fn junctions(wires: &[Wire], k: usize) -> Vec<Junction> {
+    let mut found: HashMap<Point, BTreeMap<usize, u32>> = HashMap::new();
+    for i in 0..wires.len() {
+        for j in (i + 1)..wires.len() {
+            for crossing in crossings(&wires[i], &wires[j]) {
+                let meeting = found.entry(crossing.point).or_default();
+                meeting.insert(i, crossing.steps.0);
+                meeting.insert(j, crossing.steps.1);
+            }
+        }
+    }
+    let mut junctions: Vec<Junction> = found
+        .into_iter()
+        .filter(|(_, meeting)| meeting.len() >= k.max(2))
+        .map(|(point, meeting)| Junction {
+            point,
+            wires: meeting.into_iter().collect(),
+        })
+        .collect();
+    junctions.sort_by_key(|j| (j.manhattan(), j.point));
+    junctions
+}
+
+// the puzzle's two answers, for points every one of the wires passes
+// through: the crossing nearest the origin, and the one the wires reach in
+// the fewest steps between them. The puzzle's pair of wires is answered from
+// the runs they share; any more are answered from every junction
+pub fn closest_by_manhattan(wires: &[Wire]) -> Option<(Point, i32)> {
+    if let [a, b] = wires {
+        return closest_pair(a, b).0;
+    }
+    junctions(wires, wires.len())
+        .first()
+        .map(|j| (j.point, j.manhattan()))
+}
+
+pub fn closest_by_steps(wires: &[Wire]) -> Option<(Point, u32)> {
+    if let [a, b] = wires {
+        return closest_pair(a, b).1;
+    }
+    junctions(wires, wires.len())
+        .iter()
+        .min_by_key(|j| (j.total_steps(), j.manhattan(), j.point))
+        .map(|j| (j.point, j.total_steps()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    // every point a wire passes through, with the steps to first get there
+    fn visit(instructions: &[Instruction]) -> HashMap<Point, u32> {
+        let mut visited = HashMap::new();
+        let mut at = Point::ORIGIN;
+        let mut steps = 0;
+        for i in instructions.iter() {
+            for _ in 0..i.distance {
+                at = i.direction.step(at);
+                steps += 1;
+                visited.entry(at).or_insert(steps);
+            }
+        }
+        visited
+    }
+
+    fn naive(a: &[Instruction], b: &[Instruction]) -> Vec<Crossing> {
+        let (a, b) = (visit(a), visit(b));
+        let shared: HashSet<&Point> = a.keys().filter(|p| b.contains_key(p)).collect();
+        let mut crossings: Vec<Crossing> = shared
+            .into_iter()
+            .filter(|&&p| p != Point::ORIGIN)
+            .map(|p| Crossing {
+                point: *p,
+                steps: (a[p], b[p]),
+            })
+            .collect();
+        crossings.sort_by_key(|c| (c.point.manhattan(), c.point));
+        crossings
+    }
+
+    #[test]
+    fn test_matches_naive() {
+        let pairs = [
+            "R8,U5,L5,D3\nU7,R6,D4,L4",
+            "R75,D30,R83,U83,L12,D49,R71,U7,L72\nU62,R66,U55,R34,D71,R55,D58,R83",
+            // running along each other, and back over themselves
+            "R10,L4,U3\nU0,R12,L20",
+            "R5,U5,L5,D5,R10\nD2,R3,U10",
+            // diagonals, crossing straight lines, each other, between
+            // points, and running along each other both ways
+            "NE5,D5,NW3\nU2,R6,SW4,NW2,L1",
+            "NE6,SE2\nR1,NE1,NW1,SE4,U8,L4",
+            "NE4\nR3,U3,SW2,NE5",
+            "R2,NW2,SE5\nU3,SE3,NW1,R0,SW0",
+            // diagonals spread along x, some only touching at an end
+            "NE2,R4,SE2,R4,NE2,L20\nR2,U2,R4,D3,R6,NW1,SW1",
+        ];
+        for text in pairs.iter() {
+            let wires = parse(text).unwrap();
+            let pair = [Wire::new(&wires[0]), Wire::new(&wires[1])];
+            let expected = naive(&wires[0], &wires[1]);
+            assert_eq!(crossings(&pair[0], &pair[1]), expected, "{}", text);
+            // the answers from the runs' candidates, against every point
+            assert_eq!(
+                closest_by_manhattan(&pair),
+                expected.first().map(|c| (c.point, c.point.manhattan())),
+                "{}",
+                text
+            );
+            assert_eq!(
+                closest_by_steps(&pair),
+                expected
+                    .iter()
+                    .min_by_key(|c| (c.total_steps(), c.point.manhattan(), c.point))
+                    .map(|c| (c.point, c.total_steps())),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let wires = parse(" r8, U 5 ,l5,d3\n\nne2,Sw10,R0 \n").unwrap();
+        assert_eq!(wires, parse("R8,U5,L5,D3\nNE2,SW10,R0").unwrap());
+        assert_eq!(
+            wires[1][1],
+            Instruction {
+                direction: Direction::DownLeft,
+                distance: 10
+            }
+        );
+        assert_eq!(Wire::new(&wires[1]).length(), 12);
+
+        let error = |s: &str| parse(s).unwrap_err();
+        assert_eq!(
+            error("R8,U5\n\nU7,,L4"),
+            ParseError {
+                wire: 1,
+                token: 1,
+                offset: 3,
+                error: InstructionParseError::Empty,
+            }
+        );
+        assert_eq!(
+            error("R8,  X5").error,
+            InstructionParseError::Direction("X".into())
+        );
+        assert_eq!(error("R8,  X5").offset, 5);
+        assert_eq!(
+            error("R-8").error,
+            InstructionParseError::Distance("-8".into())
+        );
+        assert_eq!(
+            error("NE").error,
+            InstructionParseError::Distance("".into())
+        );
+        assert_eq!(
+            error("U5,R99999999999").to_string(),
+            "wire 0, instruction 1 (byte 3): expected a distance, got \"99999999999\""
+        );
+        assert!(parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_wire_length() {
+        let wires = parse("R8,U5,L5,D3").unwrap();
+        assert_eq!(Wire::new(&wires[0]).length(), 21);
+        assert_eq!(Wire::default().length(), 0);
+    }
+
+    #[test]
+    fn test_junctions() {
+        // the last two run together up and down x = 5, which the first
+        // crosses at (5, 0)
+        let wires: Vec<Wire> = parse("R10\nD5,R5,U10\nU5,R5,D10")
+            .unwrap()
+            .iter()
+            .map(|w| Wire::new(w))
+            .collect();
+        let pairs = junctions(&wires, 2);
+        assert_eq!(pairs.len(), 11);
+        assert_eq!(
+            pairs
+                .iter()
+                .find(|j| j.point == Point { x: 5, y: -5 })
+                .unwrap()
+                .wires,
+            vec![(1, 20), (2, 10)]
+        );
+        let triples = junctions(&wires, 3);
+        assert_eq!(triples.len(), 1);
+        assert_eq!(triples[0].point, Point { x: 5, y: 0 });
+        assert_eq!(triples[0].wires, vec![(0, 5), (1, 15), (2, 15)]);
+        assert_eq!(triples[0].manhattan(), 5);
+        assert_eq!(triples[0].total_steps(), 35);
+        assert!(junctions(&wires, 4).is_empty());
+    }
+
+    #[test]
+    fn test_closest() {
+        let examples = [
+            ("R8,U5,L5,D3\nU7,R6,D4,L4", 6, 30),
+            (
+                "R75,D30,R83,U83,L12,D49,R71,U7,L72\nU62,R66,U55,R34,D71,R55,D58,R83",
+                159,
+                610,
+            ),
+            (
+                "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51\nU98,R91,D20,R16,D67,R40,U7,R15,U6,R7",
+                135,
+                410,
+            ),
+        ];
+        let load = |text: &str| -> Vec<Wire> {
+            parse(text).unwrap().iter().map(|w| Wire::new(w)).collect()
+        };
+        for &(text, distance, steps) in examples.iter() {
+            let wires = load(text);
+            assert_eq!(closest_by_manhattan(&wires).map(|c| c.1), Some(distance));
+            assert_eq!(closest_by_steps(&wires).map(|c| c.1), Some(steps));
+        }
+        // the origin doesn't count, however the crossings are ordered
+        let wires = load(examples[0].0);
+        assert_eq!(
+            closest_by_manhattan(&wires),
+            Some((Point { x: 3, y: -3 }, 6))
+        );
+        assert_eq!(closest_by_steps(&wires), Some((Point { x: 6, y: -5 }, 30)));
+        // a run shared for a billion steps is answered without walking it
+        let together = load("R1000000000\nL3,R1000000003");
+        assert_eq!(
+            closest_by_manhattan(&together),
+            Some((Point { x: 1, y: 0 }, 1))
+        );
+        assert_eq!(closest_by_steps(&together), Some((Point { x: 1, y: 0 }, 8)));
+        let apart = load("R5\nL5");
+        assert_eq!(closest_by_manhattan(&apart), None);
+        assert_eq!(closest_by_steps(&apart), None);
+    }
+}
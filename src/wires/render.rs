@@ -0,0 +1,215 @@
+// Pictures of the wires, for checking an answer by eye: an SVG with a colour
+// per wire, or for small inputs the grid of `+`, `-`, `|` and `X` the puzzle
+// draws, with `o` at the origin and `/` or `\` for diagonals.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::geometry::Bounds;
+
+use super::{closest_by_manhattan, closest_by_steps, junctions, Instruction, Point, Wire};
+
+// the ASCII grid is only drawn when it is at most this many cells across and
+// down
+pub const ASCII_MAX_SIDE: i32 = 400;
+
+const COLOURS: [&str; 6] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#9467bd", "#8c564b", "#e377c2",
+];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    Svg,
+    Ascii,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "svg" => Ok(Format::Svg),
+            "ascii" => Ok(Format::Ascii),
+            _ => Err(format!("unknown format {}", s)),
+        }
+    }
+}
+
+fn wires(instructions: &[Vec<Instruction>]) -> Vec<Wire> {
+    instructions.iter().map(|w| Wire::new(w)).collect()
+}
+
+// the smallest and largest corners of a box around every wire and the origin
+fn bounds(wires: &[Wire]) -> Bounds {
+    let mut bounds = Bounds::new(Point::ORIGIN);
+    for s in wires.iter().flat_map(|w| w.segments.iter()) {
+        bounds.include(s.end);
+    }
+    bounds
+}
+
+pub fn svg(instructions: &[Vec<Instruction>]) -> String {
+    let wires = wires(instructions);
+    let Bounds { low, high } = bounds(&wires);
+    // a margin, and some size for markers, even if the wires go nowhere
+    let extent = (high.x - low.x).max(high.y - low.y).max(10);
+    // at least one all round, so that wires in a straight line still leave
+    // the picture some width and height
+    let margin = (extent / 20).max(1);
+    let marker = extent / 150 + 1;
+    let (width, height) = (high.x - low.x + 2 * margin, high.y - low.y + 2 * margin);
+    let xy = |p: Point| format!("{},{}", p.x, p.y);
+
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"800\" height=\"{}\">\n",
+        low.x - margin,
+        low.y - margin,
+        width,
+        height,
+        800 * i64::from(height) / i64::from(width)
+    );
+    out += &format!(
+        "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\"/>\n",
+        low.x - margin,
+        low.y - margin,
+        width,
+        height
+    );
+    for (i, wire) in wires.iter().enumerate() {
+        let mut points = vec![xy(Point::ORIGIN)];
+        points.extend(wire.segments.iter().map(|s| xy(s.end)));
+        out += &format!(
+            "  <polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"><title>wire {}</title></polyline>\n",
+            points.join(" "),
+            COLOURS[i % COLOURS.len()],
+            marker as f64 / 3.0,
+            i
+        );
+    }
+    for junction in junctions(&wires, 2) {
+        out += &format!(
+            "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"black\"><title>{},{}</title></circle>\n",
+            junction.point.x, junction.point.y, marker, junction.point.x, junction.point.y
+        );
+    }
+    if let Some((point, distance)) = closest_by_manhattan(&wires) {
+        out += &format!(
+            "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"red\" stroke-width=\"{}\"><title>closest: {},{} (distance {})</title></circle>\n",
+            point.x, point.y, 3 * marker, marker, point.x, point.y, distance
+        );
+    }
+    if let Some((point, steps)) = closest_by_steps(&wires) {
+        out += &format!(
+            "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"green\" stroke-width=\"{}\"><title>fewest steps: {},{} ({} steps)</title></circle>\n",
+            point.x, point.y, 4 * marker, marker, point.x, point.y, steps
+        );
+    }
+    out += &format!(
+        "  <circle cx=\"0\" cy=\"0\" r=\"{}\" fill=\"red\"><title>origin</title></circle>\n",
+        2 * marker
+    );
+    out += "</svg>\n";
+    out
+}
+
+// the grid from the puzzle, with a row and column of `.` all round; None if
+// it would be more than ASCII_MAX_SIDE cells across or down
+pub fn ascii(instructions: &[Vec<Instruction>]) -> Option<String> {
+    let wires = wires(instructions);
+    let Bounds { low, high } = bounds(&wires).grow(1);
+    if low.chebyshev_to(high) + 1 > ASCII_MAX_SIDE {
+        return None;
+    }
+
+    // which wire drew each cell, and what it drew there
+    let mut cells: HashMap<Point, (usize, char)> = HashMap::new();
+    for (i, wire) in wires.iter().enumerate() {
+        let mut drawn: HashMap<Point, char> = HashMap::new();
+        for (n, s) in wire.segments.iter().enumerate() {
+            let step = s.delta();
+            let line = match (step.x, step.y) {
+                (_, 0) => '-',
+                (0, _) => '|',
+                (x, y) if x == -y => '/',
+                _ => '\\',
+            };
+            let mut at = s.start;
+            while at != s.end {
+                at += step;
+                let turn = at == s.end && n + 1 < wire.segments.len();
+                let c = if turn { '+' } else { line };
+                // a wire crossing itself isn't an intersection
+                let cell = drawn.entry(at).or_insert(c);
+                if *cell != c {
+                    *cell = '+';
+                }
+            }
+        }
+        for (at, c) in drawn {
+            cells
+                .entry(at)
+                .and_modify(|cell| *cell = (cell.0, 'X'))
+                .or_insert((i, c));
+        }
+    }
+
+    let mut out = String::new();
+    for y in low.y..=high.y {
+        for x in low.x..=high.x {
+            let at = Point { x, y };
+            out.push(match cells.get(&at) {
+                _ if at == Point::ORIGIN => 'o',
+                Some(&(_, c)) => c,
+                None => '.',
+            });
+        }
+        out.push('\n');
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse;
+    use super::*;
+
+    #[test]
+    fn test_ascii_matches_the_puzzle() {
+        let wires = parse("R8,U5,L5,D3\nU7,R6,D4,L4").unwrap();
+        assert_eq!(
+            ascii(&wires).unwrap(),
+            "...........
+.+-----+...
+.|.....|...
+.|..+--X-+.
+.|..|..|.|.
+.|.-X--+.|.
+.|..|....|.
+.|.......|.
+.o-------+.
+...........
+"
+        );
+        let far = parse("R1000\nU1").unwrap();
+        assert_eq!(ascii(&far), None);
+    }
+
+    #[test]
+    fn test_svg() {
+        let wires = parse("R8,U5,L5,D3\nU7,R6,D4,L4").unwrap();
+        let svg = svg(&wires);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert!(svg.contains("points=\"0,0 8,0 8,-5 3,-5 3,-2\""));
+        assert!(svg.contains("<title>closest: 3,-3 (distance 6)</title>"));
+        assert!(svg.contains("<title>fewest steps: 6,-5 (30 steps)</title>"));
+    }
+
+    #[test]
+    fn test_svg_of_straight_wires() {
+        // nothing but vertical wires, so the wires alone have no width
+        let upright = svg(&parse("U5\nU3").unwrap());
+        assert!(upright.contains("viewBox=\"-1 -6 2 7\" width=\"800\" height=\"2800\""));
+    }
+}